@@ -13,7 +13,7 @@ fn random_scene() -> Scene {
             radius: 1000.0,
         },
         Material::Lambertian {
-            albedo: Color::new(0.5, 0.5, 0.5),
+            albedo: Color::new(0.5, 0.5, 0.5).into(),
         },
     );
 
@@ -29,13 +29,18 @@ fn random_scene() -> Scene {
             if (center - Point3::new(4.0, 0.2, 0.0)).len() > 0.9 {
                 let sphere_material = if choose_mat < 0.8 {
                     // diffuse
-                    let albedo = Color::random_vec() * Color::random_vec();
-                    Material::Lambertian { albedo }
+                    let albedo = Color::random_vec(&mut rng) * Color::random_vec(&mut rng);
+                    Material::Lambertian {
+                        albedo: albedo.into(),
+                    }
                 } else if choose_mat < 0.95 {
                     // metal
-                    let albedo = Color::random(0.5, 1.0);
+                    let albedo = Color::random(0.5, 1.0, &mut rng);
                     let fuzz = rng.gen_range(0.0..0.5);
-                    Material::Metal { albedo, fuzz }
+                    Material::Metal {
+                        albedo: albedo.into(),
+                        fuzz,
+                    }
                 } else {
                     Material::Dielectric { ir: 1.5 }
                 };
@@ -64,7 +69,7 @@ fn random_scene() -> Scene {
             radius: 1.0,
         },
         Material::Lambertian {
-            albedo: Color::new(0.4, 0.2, 0.1),
+            albedo: Color::new(0.4, 0.2, 0.1).into(),
         },
     );
     world.add(
@@ -73,7 +78,7 @@ fn random_scene() -> Scene {
             radius: 1.0,
         },
         Material::Metal {
-            albedo: Color::new(0.7, 0.6, 0.5),
+            albedo: Color::new(0.7, 0.6, 0.5).into(),
             fuzz: 0.0,
         },
     );
@@ -116,14 +121,9 @@ fn main() -> std::io::Result<()> {
     );
 
     // Render
-    let mut renderer = Renderer::new(
-        IMAGE_WIDTH,
-        IMAGE_HEIGHT,
-        SAMPLES_PER_PIXEL,
-        MAX_DEPTH,
-        &args[1],
-    )?;
-    renderer.render(&camera, &world)?;
+    let renderer = Renderer::new(IMAGE_WIDTH, IMAGE_HEIGHT, SAMPLES_PER_PIXEL, MAX_DEPTH);
+    let image = renderer.render(&camera, &world);
 
-    Ok(())
+    // Output
+    image.save(&args[1])
 }
@@ -0,0 +1,14 @@
+use raytracer::load_scene;
+use std::{env, process::exit};
+
+fn main() -> std::io::Result<()> {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 {
+        println!("Please specify scene file and output file as command line arguments");
+        exit(-1);
+    }
+
+    let (world, camera, renderer) = load_scene(&args[1])?;
+    let image = renderer.render(&camera, &world);
+    image.save(&args[2])
+}
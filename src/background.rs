@@ -0,0 +1,95 @@
+use std::{f64::consts::PI, io, path::Path, sync::Arc};
+
+use crate::{loaders::hdr::load_hdr, Color, Vec3};
+
+/// Equirectangular environment image with linear radiance values.
+pub struct EnvironmentMap {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl EnvironmentMap {
+    /// Creates a map from pixels stored row by row, starting at the top (+Y) row.
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert_eq!(width * height, pixels.len());
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Loads a Radiance `.hdr` panorama.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let (width, height, pixels) = load_hdr(path)?;
+        Ok(Self::new(width, height, pixels))
+    }
+
+    /// Bilinearly filtered radiance coming from `direction`.
+    pub fn lookup(&self, direction: &Vec3) -> Color {
+        if self.pixels.is_empty() {
+            return Color::default();
+        }
+
+        let d = Vec3::unit_vector(direction);
+        let u = 0.5 + d.x().atan2(-d.z()) / (2.0 * PI);
+        let v = d.y().clamp(-1.0, 1.0).acos() / PI;
+
+        // Pixel centers are at half-integer coordinates
+        let x = u * self.width as f64 - 0.5;
+        let y = (v * self.height as f64 - 0.5).clamp(0.0, (self.height - 1) as f64);
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+
+        // Wrap around horizontally, clamp at the poles
+        let column = |x: f64| (x as i64).rem_euclid(self.width as i64) as usize;
+        let row = |y: f64| (y as usize).min(self.height - 1);
+        let texel = |x: f64, y: f64| self.pixels[row(y) * self.width + column(x)];
+
+        (1.0 - fy) * ((1.0 - fx) * texel(x0, y0) + fx * texel(x0 + 1.0, y0))
+            + fy * ((1.0 - fx) * texel(x0, y0 + 1.0) + fx * texel(x0 + 1.0, y0 + 1.0))
+    }
+
+    /// Get the map's width.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the map's height.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
+
+/// Radiance seen by rays that leave the scene without hitting anything.
+#[derive(Clone)]
+pub enum Background {
+    Solid { color: Color },
+    /// Blend between `bottom` (looking straight down) and `top` (looking straight up).
+    Gradient { bottom: Color, top: Color },
+    Environment { map: Arc<EnvironmentMap> },
+}
+
+impl Background {
+    pub fn color(&self, direction: &Vec3) -> Color {
+        match self {
+            Background::Solid { color } => *color,
+            Background::Gradient { bottom, top } => {
+                let unit_dir = Vec3::unit_vector(direction);
+                let t = 0.5 * (unit_dir.y() + 1.0);
+                (1.0 - t) * *bottom + t * *top
+            }
+            Background::Environment { map } => map.lookup(direction),
+        }
+    }
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::Gradient {
+            bottom: Color::new(1.0, 1.0, 1.0),
+            top: Color::new(0.5, 0.7, 1.0),
+        }
+    }
+}
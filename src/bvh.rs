@@ -0,0 +1,239 @@
+use crate::{
+    ray::Ray,
+    shapes::{Aabb, HitRecord},
+    Point3,
+};
+
+const BUCKET_COUNT: usize = 12;
+const MAX_PRIMITIVES_IN_LEAF: usize = 4;
+// Relative cost of visiting an interior node compared to intersecting a primitive.
+const TRAVERSAL_COST: f64 = 0.125;
+
+struct Node {
+    bounds: Aabb,
+    // Leaves: index of the first primitive in `indices`.
+    // Interior nodes: index of the second child (the first one directly follows the node).
+    offset: usize,
+    count: usize,
+    axis: usize,
+}
+
+/// Bounding volume hierarchy over a set of primitives identified by their index.
+///
+/// The tree is built with the surface area heuristic and stored flattened in depth-first order.
+#[derive(Default)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    indices: Vec<usize>,
+}
+
+struct BuildPrimitive {
+    index: usize,
+    bounds: Aabb,
+    centroid: Point3,
+}
+
+impl Bvh {
+    pub fn new(bounds: &[Aabb]) -> Self {
+        let mut primitives: Vec<_> = bounds
+            .iter()
+            .enumerate()
+            .map(|(index, bounds)| BuildPrimitive {
+                index,
+                bounds: *bounds,
+                centroid: bounds.centroid(),
+            })
+            .collect();
+
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(2 * bounds.len()),
+            indices: Vec::with_capacity(bounds.len()),
+        };
+        if !primitives.is_empty() {
+            bvh.build(&mut primitives);
+        }
+        bvh
+    }
+
+    /// Get the bounds of the whole hierarchy, if it is not empty.
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.nodes.first().map(|node| node.bounds)
+    }
+
+    fn build(&mut self, primitives: &mut [BuildPrimitive]) -> usize {
+        let bounds = primitives[1..].iter().fold(primitives[0].bounds, |acc, p| {
+            Aabb::surrounding(&acc, &p.bounds)
+        });
+
+        let node_index = self.nodes.len();
+        self.nodes.push(Node {
+            bounds,
+            offset: self.indices.len(),
+            count: primitives.len(),
+            axis: 0,
+        });
+
+        if primitives.len() == 1 {
+            self.indices.push(primitives[0].index);
+            return node_index;
+        }
+
+        let mut centroid_bounds = Aabb::new(primitives[0].centroid, primitives[0].centroid);
+        for p in &primitives[1..] {
+            centroid_bounds.expand(&p.centroid);
+        }
+
+        match Self::find_split(primitives, &bounds, &centroid_bounds) {
+            Some((axis, mid)) => {
+                primitives.select_nth_unstable_by(mid, |a, b| {
+                    a.centroid[axis].total_cmp(&b.centroid[axis])
+                });
+                let (left, right) = primitives.split_at_mut(mid);
+
+                self.build(left);
+                let second_child = self.build(right);
+
+                let node = &mut self.nodes[node_index];
+                node.offset = second_child;
+                node.count = 0;
+                node.axis = axis;
+            }
+            None => {
+                self.indices.extend(primitives.iter().map(|p| p.index));
+            }
+        }
+
+        node_index
+    }
+
+    /// Picks the cheapest binned SAH split. Returns the split axis and the number of primitives
+    /// that go to the first child, or `None` if making a leaf is cheaper.
+    fn find_split(
+        primitives: &[BuildPrimitive],
+        bounds: &Aabb,
+        centroid_bounds: &Aabb,
+    ) -> Option<(usize, usize)> {
+        let leaf_cost = primitives.len() as f64;
+        let mut best: Option<(f64, usize, usize)> = None;
+
+        for axis in 0..3 {
+            let min = centroid_bounds.min()[axis];
+            let extent = centroid_bounds.max()[axis] - min;
+            if extent <= 0.0 {
+                continue;
+            }
+
+            let bucket_of = |p: &BuildPrimitive| {
+                let b = (BUCKET_COUNT as f64 * (p.centroid[axis] - min) / extent) as usize;
+                b.min(BUCKET_COUNT - 1)
+            };
+
+            let mut counts = [0usize; BUCKET_COUNT];
+            let mut bucket_bounds: [Option<Aabb>; BUCKET_COUNT] = [None; BUCKET_COUNT];
+            for p in primitives {
+                let b = bucket_of(p);
+                counts[b] += 1;
+                bucket_bounds[b] = Some(match bucket_bounds[b] {
+                    Some(acc) => Aabb::surrounding(&acc, &p.bounds),
+                    None => p.bounds,
+                });
+            }
+
+            // Sweep from the right to get the area of everything past each split plane.
+            let mut right_area = [0.0; BUCKET_COUNT];
+            let mut right_count = [0usize; BUCKET_COUNT];
+            let mut acc: Option<Aabb> = None;
+            let mut count = 0;
+            for b in (1..BUCKET_COUNT).rev() {
+                acc = Self::merge(acc, bucket_bounds[b]);
+                count += counts[b];
+                right_area[b] = acc.map_or(0.0, |a| a.surface_area());
+                right_count[b] = count;
+            }
+
+            let mut acc: Option<Aabb> = None;
+            let mut count = 0;
+            for split in 1..BUCKET_COUNT {
+                acc = Self::merge(acc, bucket_bounds[split - 1]);
+                count += counts[split - 1];
+                if count == 0 || right_count[split] == 0 {
+                    continue;
+                }
+                let left_area = acc.map_or(0.0, |a| a.surface_area());
+                let cost = TRAVERSAL_COST
+                    + (count as f64 * left_area + right_count[split] as f64 * right_area[split])
+                        / bounds.surface_area();
+                if best.is_none_or(|(best_cost, _, _)| cost < best_cost) {
+                    best = Some((cost, axis, count));
+                }
+            }
+        }
+
+        match best {
+            Some((cost, axis, mid))
+                if cost < leaf_cost || primitives.len() > MAX_PRIMITIVES_IN_LEAF =>
+            {
+                Some((axis, mid))
+            }
+            // All centroids coincide, but the leaf would be too big: split in the middle.
+            None if primitives.len() > MAX_PRIMITIVES_IN_LEAF => Some((0, primitives.len() / 2)),
+            _ => None,
+        }
+    }
+
+    fn merge(acc: Option<Aabb>, other: Option<Aabb>) -> Option<Aabb> {
+        match (acc, other) {
+            (Some(a), Some(b)) => Some(Aabb::surrounding(&a, &b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Finds the closest primitive hit by the ray. `hit_primitive` is called with the index of
+    /// every candidate primitive and the current search interval.
+    pub fn hit<F>(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        mut hit_primitive: F,
+    ) -> Option<(usize, HitRecord)>
+    where
+        F: FnMut(usize, &Ray, f64, f64) -> Option<HitRecord>,
+    {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let dir_is_neg = [r.dir().x() < 0.0, r.dir().y() < 0.0, r.dir().z() < 0.0];
+        let mut result = None;
+        let mut closest_so_far = t_max;
+
+        let mut stack = Vec::with_capacity(64);
+        stack.push(0);
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !node.bounds.hit(r, t_min, closest_so_far) {
+                continue;
+            }
+
+            if node.count > 0 {
+                for &index in &self.indices[node.offset..node.offset + node.count] {
+                    if let Some(rec) = hit_primitive(index, r, t_min, closest_so_far) {
+                        closest_so_far = rec.t();
+                        result = Some((index, rec));
+                    }
+                }
+            } else if dir_is_neg[node.axis] {
+                // Visit the second child first so that closer hits shrink the interval early.
+                stack.push(node_index + 1);
+                stack.push(node.offset);
+            } else {
+                stack.push(node.offset);
+                stack.push(node_index + 1);
+            }
+        }
+
+        result
+    }
+}
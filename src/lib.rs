@@ -2,6 +2,25 @@ mod scene;
 pub type Scene = scene::Scene;
 
 mod ray;
+pub type Ray = ray::Ray;
+
+mod background;
+pub type Background = background::Background;
+pub type EnvironmentMap = background::EnvironmentMap;
+
+mod bvh;
+
+mod transform;
+pub type Transform = transform::Transform;
+
+mod medium;
+pub type Medium = medium::Medium;
+
+mod voxel_grid;
+pub type VoxelGrid = voxel_grid::VoxelGrid;
+
+mod light;
+pub type Light = light::Light;
 
 mod vec3;
 pub type Vec3 = vec3::Vec3;
@@ -11,12 +30,41 @@ pub type Color = vec3::Vec3;
 mod render;
 pub type Renderer = render::renderer::Renderer;
 pub type Camera = render::camera::Camera;
+pub type Framebuffer = render::framebuffer::Framebuffer;
+pub type Tile = render::tile::Tile;
+pub type CancellationToken = render::observer::CancellationToken;
+pub type ProgressBarObserver = render::observer::ProgressBarObserver;
+pub use render::observer::RenderObserver;
+pub type AdaptiveSampling = render::adaptive::AdaptiveSampling;
+pub type SamplerKind = render::sampler::SamplerKind;
+pub use render::sampler::Sampler;
+pub type OutputFormat = render::output::OutputFormat;
+pub type ToneMapping = render::tonemap::ToneMapping;
+pub type ToneMapOperator = render::tonemap::ToneMapOperator;
+pub type TransferFunction = render::tonemap::TransferFunction;
 
 mod materials;
 pub type Material = materials::Material;
+pub type BsdfSample = materials::BsdfSample;
+pub use materials::Scatter;
+
+mod textures;
+pub type Texture = textures::Texture;
+pub type ImageTexture = textures::ImageTexture;
+pub type Perlin = textures::Perlin;
+pub use textures::TextureValue;
 
 mod shapes;
 pub type Shape = shapes::Shape;
+pub type Aabb = shapes::Aabb;
+pub type HitRecord = shapes::HitRecord;
+pub type Mesh = shapes::Mesh;
+pub type MeshFace = shapes::MeshFace;
+pub use shapes::Hittable;
 
 mod object;
 pub type Object = object::Object;
+
+mod loaders;
+pub use loaders::obj::load_obj;
+pub use loaders::scene::load_scene;
@@ -0,0 +1,128 @@
+use std::f64::consts::PI;
+
+use crate::{
+    ray::Ray,
+    render::sampler::Sampler,
+    shapes::{Hittable, Quad},
+    Color, Point3, Vec3,
+};
+
+/// Light source that the renderer samples directly with shadow rays.
+#[derive(Debug, Clone, Copy)]
+pub enum Light {
+    /// Infinitely small light emitting `intensity` equally in all directions.
+    Point { position: Point3, intensity: Color },
+    /// Sphere with an emissive material, registered by [`crate::Scene::add`].
+    Sphere { center: Point3, radius: f64 },
+    /// Quad with an emissive material, registered by [`crate::Scene::add`]. Only its front
+    /// side emits light.
+    Quad { origin: Point3, u: Vec3, v: Vec3 },
+}
+
+/// Direction towards a light picked by [`Light::sample`].
+pub(crate) struct LightSample {
+    /// Unit direction from the shaded point towards the light.
+    pub wi: Vec3,
+    /// Solid angle density of `wi`, `None` for lights that can only be reached by sampling them.
+    pub pdf: Option<f64>,
+    /// Distance to the light along `wi`, infinite if it has to be found by tracing a ray.
+    pub distance: f64,
+}
+
+impl Light {
+    /// Picks a direction from `p` towards the light, or `None` if it cannot be seen from `p`.
+    pub(crate) fn sample(&self, p: &Point3, sampler: &mut dyn Sampler) -> Option<LightSample> {
+        match self {
+            Light::Point { position, .. } => {
+                let to_light = *position - *p;
+                let distance = to_light.len();
+                Some(LightSample {
+                    wi: to_light / distance,
+                    pdf: None,
+                    distance,
+                })
+            }
+            Light::Sphere { center, radius } => {
+                let to_center = *center - *p;
+                let distance_squared = to_center.len_squared();
+                if distance_squared <= radius * radius {
+                    // Shading points inside the light cannot use cone sampling
+                    return None;
+                }
+
+                // Uniformly sample the cone of directions subtended by the sphere
+                let (u1, u2) = sampler.get_2d();
+                let cos_theta_max = (1.0 - radius * radius / distance_squared).sqrt();
+                let cos_theta = 1.0 + u1 * (cos_theta_max - 1.0);
+                let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+                let phi = 2.0 * PI * u2;
+
+                let w = Vec3::unit_vector(&to_center);
+                let (u, v) = Vec3::orthonormal_basis(&w);
+                let wi = sin_theta * phi.cos() * u + sin_theta * phi.sin() * v + cos_theta * w;
+
+                Some(LightSample {
+                    wi,
+                    pdf: Some(Self::cone_pdf(cos_theta_max)),
+                    distance: f64::INFINITY,
+                })
+            }
+            Light::Quad { origin, u, v } => {
+                // Uniformly sample the area and convert the density to solid angle
+                let quad = Quad::new(*origin, *u, *v);
+                let (a, b) = sampler.get_2d();
+                let to_light = quad.point(a, b) - *p;
+                let distance_squared = to_light.len_squared();
+                let wi = to_light / distance_squared.sqrt();
+                let cosine = -Vec3::dot(&quad.normal(), &wi);
+                if cosine <= 0.0 {
+                    return None;
+                }
+
+                Some(LightSample {
+                    wi,
+                    pdf: Some(distance_squared / (cosine * quad.area())),
+                    distance: f64::INFINITY,
+                })
+            }
+        }
+    }
+
+    /// Solid angle density with which [`Light::sample`] picks `wi` from `p`.
+    pub(crate) fn pdf(&self, p: &Point3, wi: &Vec3) -> f64 {
+        match self {
+            Light::Point { .. } => 0.0,
+            Light::Sphere { center, radius } => {
+                let to_center = *center - *p;
+                let distance_squared = to_center.len_squared();
+                if distance_squared <= radius * radius {
+                    return 0.0;
+                }
+
+                let cos_theta_max = (1.0 - radius * radius / distance_squared).sqrt();
+                let cos_theta =
+                    Vec3::dot(&Vec3::unit_vector(wi), &to_center) / distance_squared.sqrt();
+                if cos_theta < cos_theta_max {
+                    return 0.0;
+                }
+                Self::cone_pdf(cos_theta_max)
+            }
+            Light::Quad { origin, u, v } => {
+                let quad = Quad::new(*origin, *u, *v);
+                let wi = Vec3::unit_vector(wi);
+                let cosine = -Vec3::dot(&quad.normal(), &wi);
+                if cosine <= 0.0 {
+                    return 0.0;
+                }
+                match quad.hit(&Ray::new(*p, wi), 0.0, f64::INFINITY) {
+                    Some(rec) => rec.t() * rec.t() / (cosine * quad.area()),
+                    None => 0.0,
+                }
+            }
+        }
+    }
+
+    fn cone_pdf(cos_theta_max: f64) -> f64 {
+        1.0 / (2.0 * PI * (1.0 - cos_theta_max))
+    }
+}
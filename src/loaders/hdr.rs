@@ -0,0 +1,139 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use crate::Color;
+
+/// Decodes a Radiance RGBE (`.hdr`) image into linear colors, stored row by row from the top.
+///
+/// Returns the image width, height and pixels.
+pub fn load_hdr<P: AsRef<Path>>(path: P) -> io::Result<(usize, usize, Vec<Color>)> {
+    let path = path.as_ref();
+    let data = fs::read(path)?;
+    decode(&data).map_err(|message| {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), message),
+        )
+    })
+}
+
+fn decode(data: &[u8]) -> Result<(usize, usize, Vec<Color>), String> {
+    let mut pos = 0;
+    let mut next_line = || -> Result<&[u8], String> {
+        let start = pos;
+        let len = data[start..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| "unexpected end of header".to_string())?;
+        pos = start + len + 1;
+        Ok(&data[start..start + len])
+    };
+
+    let magic = next_line()?;
+    if !magic.starts_with(b"#?") {
+        return Err("not a Radiance HDR file".to_string());
+    }
+
+    loop {
+        let line = next_line()?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some(format) = line.strip_prefix(b"FORMAT=") {
+            if format != b"32-bit_rle_rgbe" {
+                return Err(format!(
+                    "unsupported pixel format '{}'",
+                    String::from_utf8_lossy(format)
+                ));
+            }
+        }
+    }
+
+    let resolution = String::from_utf8_lossy(next_line()?).into_owned();
+    let fields: Vec<_> = resolution.split_whitespace().collect();
+    let (height, width) = match fields.as_slice() {
+        ["-Y", height, "+X", width] => (
+            height.parse::<usize>().map_err(|e| e.to_string())?,
+            width.parse::<usize>().map_err(|e| e.to_string())?,
+        ),
+        _ => return Err(format!("unsupported image orientation '{}'", resolution)),
+    };
+
+    let mut pixels = Vec::with_capacity(width * height);
+    let mut scanline = vec![[0u8; 4]; width];
+    let mut rest = &data[pos..];
+    for _ in 0..height {
+        rest = read_scanline(rest, &mut scanline)?;
+        pixels.extend(scanline.iter().map(rgbe_to_color));
+    }
+
+    Ok((width, height, pixels))
+}
+
+fn read_scanline<'a>(data: &'a [u8], scanline: &mut [[u8; 4]]) -> Result<&'a [u8], String> {
+    let width = scanline.len();
+    let truncated = || "unexpected end of pixel data".to_string();
+
+    let is_rle = (8..0x8000).contains(&width)
+        && data.len() >= 4
+        && data[0] == 2
+        && data[1] == 2
+        && data[2] & 0x80 == 0;
+    if !is_rle {
+        // Flat scanline
+        let bytes = data.get(..4 * width).ok_or_else(truncated)?;
+        for (pixel, chunk) in scanline.iter_mut().zip(bytes.chunks_exact(4)) {
+            pixel.copy_from_slice(chunk);
+        }
+        return Ok(&data[4 * width..]);
+    }
+
+    if ((data[2] as usize) << 8 | data[3] as usize) != width {
+        return Err("scanline width mismatch".to_string());
+    }
+
+    // Each channel is run-length encoded separately
+    let mut pos = 4;
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let count = *data.get(pos).ok_or_else(truncated)? as usize;
+            pos += 1;
+            if count > 128 {
+                let count = count - 128;
+                let value = *data.get(pos).ok_or_else(truncated)?;
+                pos += 1;
+                if x + count > width {
+                    return Err("run exceeds scanline".to_string());
+                }
+                for pixel in &mut scanline[x..x + count] {
+                    pixel[channel] = value;
+                }
+                x += count;
+            } else {
+                if count == 0 || x + count > width {
+                    return Err("invalid literal run".to_string());
+                }
+                let values = data.get(pos..pos + count).ok_or_else(truncated)?;
+                pos += count;
+                for (pixel, &value) in scanline[x..x + count].iter_mut().zip(values) {
+                    pixel[channel] = value;
+                }
+                x += count;
+            }
+        }
+    }
+
+    Ok(&data[pos..])
+}
+
+fn rgbe_to_color(rgbe: &[u8; 4]) -> Color {
+    if rgbe[3] == 0 {
+        return Color::default();
+    }
+    let f = 2f64.powi(rgbe[3] as i32 - (128 + 8));
+    Color::new(rgbe[0] as f64 * f, rgbe[1] as f64 * f, rgbe[2] as f64 * f)
+}
@@ -0,0 +1,105 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use crate::Color;
+
+use super::hdr::load_hdr;
+
+/// Decodes a PNG, JPEG or Radiance HDR image into linear colors, stored row by row from the
+/// top. 8 and 16 bit images are assumed to be sRGB encoded. The format is detected from the
+/// file contents.
+///
+/// Returns the image width, height and pixels.
+pub fn load_image<P: AsRef<Path>>(path: P) -> io::Result<(usize, usize, Vec<Color>)> {
+    let path = path.as_ref();
+    let data = fs::read(path)?;
+
+    let result = if data.starts_with(b"\x89PNG") {
+        decode_png(&data)
+    } else if data.starts_with(&[0xff, 0xd8]) {
+        decode_jpeg(&data)
+    } else if data.starts_with(b"#?") {
+        return load_hdr(path);
+    } else {
+        Err("unsupported image format".to_string())
+    };
+
+    result.map_err(|message| {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), message),
+        )
+    })
+}
+
+fn decode_png(data: &[u8]) -> Result<(usize, usize, Vec<Color>), String> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| e.to_string())?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).map_err(|e| e.to_string())?;
+
+    let channels = match info.color_type {
+        png::ColorType::Grayscale => 1,
+        png::ColorType::GrayscaleAlpha => 2,
+        png::ColorType::Rgb => 3,
+        png::ColorType::Rgba => 4,
+        png::ColorType::Indexed => return Err("unexpanded palette".to_string()),
+    };
+    let width = info.width as usize;
+    let height = info.height as usize;
+
+    let pixels = buffer[..info.buffer_size()]
+        .chunks_exact(info.line_size)
+        .flat_map(|line| line[..width * channels].chunks_exact(channels))
+        .map(|texel| match channels {
+            // Alpha is ignored
+            1 | 2 => srgb_color(texel[0], texel[0], texel[0]),
+            _ => srgb_color(texel[0], texel[1], texel[2]),
+        })
+        .collect();
+
+    Ok((width, height, pixels))
+}
+
+fn decode_jpeg(data: &[u8]) -> Result<(usize, usize, Vec<Color>), String> {
+    let mut decoder = jpeg_decoder::Decoder::new(data);
+    let buffer = decoder.decode().map_err(|e| e.to_string())?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| "missing image header".to_string())?;
+
+    let pixels = match info.pixel_format {
+        jpeg_decoder::PixelFormat::L8 => buffer.iter().map(|&l| srgb_color(l, l, l)).collect(),
+        // Big endian, only the high byte matters for 8 bit display values
+        jpeg_decoder::PixelFormat::L16 => buffer
+            .chunks_exact(2)
+            .map(|l| srgb_color(l[0], l[0], l[0]))
+            .collect(),
+        jpeg_decoder::PixelFormat::RGB24 => buffer
+            .chunks_exact(3)
+            .map(|c| srgb_color(c[0], c[1], c[2]))
+            .collect(),
+        jpeg_decoder::PixelFormat::CMYK32 => {
+            return Err("CMYK images are not supported".to_string())
+        }
+    };
+
+    Ok((info.width as usize, info.height as usize, pixels))
+}
+
+fn srgb_color(r: u8, g: u8, b: u8) -> Color {
+    Color::new(srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b))
+}
+
+fn srgb_to_linear(value: u8) -> f64 {
+    let v = value as f64 / 255.0;
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
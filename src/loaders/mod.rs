@@ -0,0 +1,5 @@
+pub mod hdr;
+pub mod image;
+pub mod obj;
+pub mod scene;
+pub mod voxels;
@@ -0,0 +1,481 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+    sync::Arc,
+};
+
+use crate::{
+    shapes::{Mesh, MeshFace},
+    Color, Material, Point3, Shape, Texture, Vec3,
+};
+
+const DEFAULT_MATERIAL: Material = Material::Lambertian {
+    albedo: Texture::Solid {
+        color: Color::new(0.5, 0.5, 0.5),
+    },
+};
+
+/// A corner of an OBJ face: indices into the file-wide position, uv and normal lists.
+#[derive(Clone, Copy)]
+struct Corner {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+#[derive(Default)]
+struct Group {
+    faces: Vec<[Corner; 3]>,
+}
+
+/// Loads a Wavefront OBJ file together with the MTL libraries it references.
+///
+/// Faces are grouped by material and every group becomes one [`Shape::Mesh`], so the result
+/// can be added to a [`crate::Scene`] directly. Polygons are triangulated as fans. Statements
+/// that do not describe faces (lines, points, curves, ...) are skipped, and faces whose MTL
+/// library cannot be found get a gray Lambertian material.
+pub fn load_obj<P: AsRef<Path>>(path: P) -> io::Result<Vec<(Shape, Material)>> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path)?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    let mut positions: Vec<Point3> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<(f64, f64)> = Vec::new();
+
+    let mut materials: HashMap<String, Material> = HashMap::new();
+    let mut groups: Vec<(Option<String>, Group)> = vec![(None, Group::default())];
+    let mut current = 0;
+    let mut missing_library = false;
+
+    for (line_index, line) in source.lines().enumerate() {
+        let error = |message: String| parse_error(path, line_index + 1, message);
+
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) if !keyword.starts_with('#') => keyword,
+            _ => continue,
+        };
+        let args: Vec<_> = tokens.collect();
+
+        match keyword {
+            "v" => positions.push(parse_vec3(&args).map_err(error)?),
+            "vn" => {
+                let normal = parse_vec3(&args).map_err(error)?;
+                if normal.len_squared() == 0.0 {
+                    return Err(error("zero-length normal".to_string()));
+                }
+                normals.push(Vec3::unit_vector(&normal));
+            }
+            "vt" => {
+                let u = parse_float(args.first().copied()).map_err(error)?;
+                let v = args
+                    .get(1)
+                    .map_or(Ok(0.0), |v| parse_float(Some(v)))
+                    .map_err(error)?;
+                uvs.push((u, v));
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(error("face needs at least 3 vertices".to_string()));
+                }
+                let corners = args
+                    .iter()
+                    .map(|arg| parse_corner(arg, positions.len(), uvs.len(), normals.len()))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+                for i in 1..corners.len() - 1 {
+                    groups[current]
+                        .1
+                        .faces
+                        .push([corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            "usemtl" => {
+                let name = args.join(" ");
+                current = match groups.iter().position(|(n, _)| n.as_deref() == Some(&name)) {
+                    Some(index) => index,
+                    None => {
+                        groups.push((Some(name), Group::default()));
+                        groups.len() - 1
+                    }
+                };
+            }
+            "mtllib" => {
+                for library in &args {
+                    match load_mtl(&base_dir.join(library)) {
+                        Ok(library) => materials.extend(library),
+                        // Exporters often reference libraries that are not shipped with the
+                        // model; their faces are rendered with the default material instead.
+                        Err(e) if e.kind() == ErrorKind::NotFound => missing_library = true,
+                        Err(e) => return Err(e),
+                    }
+                }
+            }
+            // Object names, groups, smoothing groups, lines, points and free-form geometry
+            // do not affect rendering.
+            _ => {}
+        }
+    }
+
+    let mut result = Vec::new();
+    for (name, group) in groups {
+        if group.faces.is_empty() {
+            continue;
+        }
+        let material = match name {
+            Some(name) => match materials.get(&name) {
+                Some(material) => material.clone(),
+                None if missing_library => DEFAULT_MATERIAL,
+                None => {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        format!("{}: unknown material '{}'", path.display(), name),
+                    ))
+                }
+            },
+            None => DEFAULT_MATERIAL,
+        };
+        let mesh = build_mesh(&positions, &normals, &uvs, &group);
+        result.push((
+            Shape::Mesh {
+                mesh: Arc::new(mesh),
+            },
+            material,
+        ));
+    }
+
+    Ok(result)
+}
+
+/// Copies the vertex data used by a group into compact buffers of its own mesh.
+fn build_mesh(positions: &[Point3], normals: &[Vec3], uvs: &[(f64, f64)], group: &Group) -> Mesh {
+    fn remap<T: Copy>(
+        index: usize,
+        source: &[T],
+        target: &mut Vec<T>,
+        map: &mut HashMap<usize, usize>,
+    ) -> usize {
+        *map.entry(index).or_insert_with(|| {
+            target.push(source[index]);
+            target.len() - 1
+        })
+    }
+
+    let mut mesh_positions = Vec::new();
+    let mut mesh_normals = Vec::new();
+    let mut mesh_uvs = Vec::new();
+    let mut position_map = HashMap::new();
+    let mut normal_map = HashMap::new();
+    let mut uv_map = HashMap::new();
+
+    let faces = group
+        .faces
+        .iter()
+        .map(|corners| {
+            let face_positions = corners.map(|c| {
+                remap(
+                    c.position,
+                    positions,
+                    &mut mesh_positions,
+                    &mut position_map,
+                )
+            });
+            let face_normals = if corners.iter().all(|c| c.normal.is_some()) {
+                Some(corners.map(|c| {
+                    remap(
+                        c.normal.unwrap(),
+                        normals,
+                        &mut mesh_normals,
+                        &mut normal_map,
+                    )
+                }))
+            } else {
+                None
+            };
+            let face_uvs = if corners.iter().all(|c| c.uv.is_some()) {
+                Some(corners.map(|c| remap(c.uv.unwrap(), uvs, &mut mesh_uvs, &mut uv_map)))
+            } else {
+                None
+            };
+            MeshFace::new(face_positions, face_normals, face_uvs)
+        })
+        .collect();
+
+    Mesh::new(mesh_positions, mesh_normals, mesh_uvs, faces)
+}
+
+#[derive(Default)]
+struct MtlEntry {
+    diffuse: Option<Color>,
+    specular: Option<Color>,
+    shininess: Option<f64>,
+    ior: Option<f64>,
+    dissolve: Option<f64>,
+    transmission: Option<Color>,
+    emission: Option<Color>,
+    illum: Option<u32>,
+}
+
+impl MtlEntry {
+    /// Maps the MTL parameters onto the closest of our materials.
+    fn to_material(&self) -> Material {
+        if let Some(emit) = self.emission.filter(|ke| max_component(ke) > 0.0) {
+            return Material::DiffuseLight { emit };
+        }
+
+        let is_transparent = self.dissolve.is_some_and(|d| d < 1.0)
+            // A transmission filter on its own means clear glass; many exporters also
+            // write `Tf 1 1 1` for opaque materials, so only trust it without a diffuse color.
+            || (self.transmission.is_some_and(|tf| max_component(&tf) > 0.0)
+                && self.diffuse.is_none_or(|kd| max_component(&kd) == 0.0))
+            || matches!(self.illum, Some(4 | 6 | 7 | 9));
+        if is_transparent {
+            return Material::Dielectric {
+                ir: self.ior.unwrap_or(1.5),
+            };
+        }
+
+        let diffuse = self.diffuse.unwrap_or(Color::new(0.5, 0.5, 0.5));
+        let specular = self.specular.unwrap_or_default();
+        let is_metal = self.illum == Some(3) || max_component(&specular) > max_component(&diffuse);
+        if is_metal {
+            // Map the Phong exponent to a roughness in [0, 1].
+            let fuzz = (2.0 / (self.shininess.unwrap_or(0.0).max(0.0) + 2.0)).sqrt();
+            return Material::Metal {
+                albedo: specular.into(),
+                fuzz,
+            };
+        }
+
+        Material::Lambertian {
+            albedo: diffuse.into(),
+        }
+    }
+}
+
+fn load_mtl(path: &Path) -> io::Result<HashMap<String, Material>> {
+    let source = fs::read_to_string(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+
+    let mut entries: Vec<(String, MtlEntry)> = Vec::new();
+
+    for (line_index, line) in source.lines().enumerate() {
+        let error = |message: String| parse_error(path, line_index + 1, message);
+
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) if !keyword.starts_with('#') => keyword,
+            _ => continue,
+        };
+        let args: Vec<_> = tokens.collect();
+
+        if keyword == "newmtl" {
+            entries.push((args.join(" "), MtlEntry::default()));
+            continue;
+        }
+
+        let entry = match entries.last_mut() {
+            Some((_, entry)) => entry,
+            None => return Err(error(format!("'{}' before any 'newmtl'", keyword))),
+        };
+
+        match keyword {
+            "Kd" => entry.diffuse = Some(parse_vec3(&args).map_err(error)?),
+            "Ks" => entry.specular = Some(parse_vec3(&args).map_err(error)?),
+            "Ke" => entry.emission = Some(parse_vec3(&args).map_err(error)?),
+            "Tf" => entry.transmission = Some(parse_vec3(&args).map_err(error)?),
+            "Ns" => entry.shininess = Some(parse_float(args.first().copied()).map_err(error)?),
+            "Ni" => entry.ior = Some(parse_float(args.first().copied()).map_err(error)?),
+            "d" => entry.dissolve = Some(parse_float(args.first().copied()).map_err(error)?),
+            "Tr" => entry.dissolve = Some(1.0 - parse_float(args.first().copied()).map_err(error)?),
+            "illum" => {
+                let illum = args
+                    .first()
+                    .and_then(|v| v.parse().ok())
+                    .ok_or_else(|| error("expected an illumination model number".to_string()))?;
+                entry.illum = Some(illum);
+            }
+            // Everything else (ambient color, texture maps, ...) is not supported and ignored.
+            _ => {}
+        }
+    }
+
+    Ok(entries
+        .into_iter()
+        .map(|(name, entry)| (name, entry.to_material()))
+        .collect())
+}
+
+fn parse_error(path: &Path, line: usize, message: String) -> io::Error {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!("{}:{}: {}", path.display(), line, message),
+    )
+}
+
+fn parse_float(token: Option<&str>) -> Result<f64, String> {
+    let token = token.ok_or_else(|| "missing number".to_string())?;
+    token
+        .parse()
+        .map_err(|_| format!("invalid number '{}'", token))
+}
+
+fn parse_vec3(args: &[&str]) -> Result<Vec3, String> {
+    if args.len() < 3 {
+        return Err(format!("expected 3 numbers, got {}", args.len()));
+    }
+    Ok(Vec3::new(
+        parse_float(Some(args[0]))?,
+        parse_float(Some(args[1]))?,
+        parse_float(Some(args[2]))?,
+    ))
+}
+
+/// Parses a `v`, `v/vt`, `v//vn` or `v/vt/vn` face corner. Negative indices are relative to
+/// the end of the lists read so far.
+fn parse_corner(
+    token: &str,
+    positions: usize,
+    uvs: usize,
+    normals: usize,
+) -> Result<Corner, String> {
+    let resolve =
+        |index: Option<&str>, count: usize, what: &str| -> Result<Option<usize>, String> {
+            let index = match index {
+                Some(index) if !index.is_empty() => index,
+                _ => return Ok(None),
+            };
+            let value: i64 = index
+                .parse()
+                .map_err(|_| format!("invalid {} index '{}'", what, index))?;
+            let resolved = if value > 0 {
+                value - 1
+            } else {
+                count as i64 + value
+            };
+            if value == 0 || resolved < 0 || resolved >= count as i64 {
+                return Err(format!("{} index {} is out of range", what, value));
+            }
+            Ok(Some(resolved as usize))
+        };
+
+    let mut parts = token.split('/');
+    let position = resolve(parts.next(), positions, "vertex")?
+        .ok_or_else(|| format!("missing vertex index in '{}'", token))?;
+    let uv = resolve(parts.next(), uvs, "texture coordinate")?;
+    let normal = resolve(parts.next(), normals, "normal")?;
+
+    Ok(Corner {
+        position,
+        uv,
+        normal,
+    })
+}
+
+fn max_component(c: &Color) -> f64 {
+    c.x().max(c.y()).max(c.z())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `files` to a fresh directory and loads the first one as an OBJ.
+    fn load(name: &str, files: &[(&str, &str)]) -> io::Result<Vec<(Shape, Material)>> {
+        let dir =
+            std::env::temp_dir().join(format!("raytracer-obj-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir)?;
+        for (file, contents) in files {
+            fs::write(dir.join(file), contents)?;
+        }
+        let result = load_obj(dir.join(files[0].0));
+        fs::remove_dir_all(&dir)?;
+        result
+    }
+
+    #[test]
+    fn skips_unsupported_statements() {
+        let obj = "\
+o quad
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vp 0.5 0.5
+l 1 2
+p 3
+cstype bspline
+deg 3
+curv 0 1 1 2 3 4
+end
+mg 1 0.5
+usemap none
+f 1 2 3 4
+";
+        let objects = load("skip", &[("quad.obj", obj)]).unwrap();
+        assert_eq!(objects.len(), 1);
+        assert!(matches!(
+            objects[0],
+            (Shape::Mesh { .. }, Material::Lambertian { .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_zero_length_normals() {
+        let obj = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+vn 0 0 1
+vn 0 0 0
+f 1//1 2//1 3//2
+";
+        let error = load("zero-normal", &[("triangle.obj", obj)]).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(
+            error
+                .to_string()
+                .ends_with("triangle.obj:5: zero-length normal"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn missing_library_falls_back_to_default_material() {
+        let obj = "\
+mtllib missing.mtl
+v 0 0 0
+v 1 0 0
+v 0 1 0
+usemtl red
+f 1 2 3
+";
+        let objects = load("missing-mtl", &[("triangle.obj", obj)]).unwrap();
+        assert_eq!(objects.len(), 1);
+        match &objects[0].1 {
+            Material::Lambertian {
+                albedo: Texture::Solid { color },
+            } => assert_eq!([color.x(), color.y(), color.z()], [0.5; 3]),
+            _ => panic!("expected the default material"),
+        }
+    }
+
+    #[test]
+    fn loads_materials_from_library() {
+        let obj = "\
+mtllib scene.mtl
+v 0 0 0
+v 1 0 0
+v 0 1 0
+usemtl glass
+f 1 2 3
+";
+        let mtl = "newmtl glass\nNi 1.33\nd 0.2\n";
+        let objects = load("mtl", &[("triangle.obj", obj), ("scene.mtl", mtl)]).unwrap();
+        assert!(matches!(objects[0].1, Material::Dielectric { ir } if ir == 1.33));
+    }
+}
@@ -0,0 +1,1422 @@
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::{self, ErrorKind},
+    marker::PhantomData,
+    num::{NonZeroU32, NonZeroUsize},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use serde::{
+    de::{
+        self,
+        value::{MapAccessDeserializer, SeqAccessDeserializer, StrDeserializer},
+        DeserializeOwned, DeserializeSeed, EnumAccess, IgnoredAny, IntoDeserializer, MapAccess,
+        SeqAccess, Unexpected, VariantAccess, Visitor,
+    },
+    forward_to_deserialize_any, Deserialize, Deserializer,
+};
+
+use crate::{
+    AdaptiveSampling, Background, Camera, Color, EnvironmentMap, ImageTexture, Material, Renderer,
+    SamplerKind, Scene, Shape, Texture, Transform, Vec3, VoxelGrid,
+};
+
+use super::obj::load_obj;
+
+/// Loads a scene description written in TOML, JSON or YAML, picked by the file extension.
+///
+/// The description has a `camera` and a `renderer` section, and optionally a `background`,
+/// a list of `objects` and a list of point `lights`. Vectors and colors are arrays of three
+/// numbers. Every object has a `shape` and a `material`, both selected by their `type` field,
+/// which has to come first, and an optional list of `transform` steps applied in order. For
+/// example, in TOML:
+///
+/// ```toml
+/// [camera]
+/// look_from = [13, 2, 3]
+/// look_at = [0, 0, 0]
+/// vfov = 20
+///
+/// [renderer]
+/// width = 400
+/// height = 225
+/// samples_per_pixel = 64
+///
+/// [[objects]]
+/// shape = { type = "sphere", center = [0, 1, 0], radius = 1 }
+/// material = { type = "lambertian", albedo = [0.4, 0.2, 0.1] }
+/// transform = [{ scale = [1, 2, 1] }, { rotate_y = 30 }]
+/// ```
+///
+/// Textures are either colors or tables such as `{ type = "checker", scale = 0.5, even = [..],
+/// odd = [..] }`. Images, OBJ meshes and voxel grids are referenced by paths relative to the
+/// scene file. Invalid descriptions are reported with the line they were found on, and files
+/// that fail to load with the line of the object referencing them.
+pub fn load_scene<P: AsRef<Path>>(path: P) -> io::Result<(Scene, Camera, Renderer)> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path)?;
+    let invalid = |message: String| {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), message.trim_end()),
+        )
+    };
+
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    let format = Format::from_extension(&extension)
+        .ok_or_else(|| invalid(format!("unsupported scene format '{}'", extension)))?;
+    let description: SceneDescription = format.parse(&source).map_err(invalid)?;
+
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    description.build(base_dir).map_err(|(item, e)| {
+        let location = match format.line_of(&source, item) {
+            Some(line) => format!("{}:{}", path.display(), line),
+            None => path.display().to_string(),
+        };
+        io::Error::new(e.kind(), format!("{}: {}: {}", location, item, e))
+    })
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl Format {
+    fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "toml" => Some(Format::Toml),
+            "json" => Some(Format::Json),
+            "yaml" | "yml" => Some(Format::Yaml),
+            _ => None,
+        }
+    }
+
+    fn parse<T: DeserializeOwned>(self, source: &str) -> Result<T, String> {
+        match self {
+            Format::Toml => toml::from_str(source).map_err(|e| e.to_string()),
+            Format::Json => serde_json::from_str(source).map_err(|e| e.to_string()),
+            Format::Yaml => serde_yaml::from_str(source).map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Line on which `item` starts. The formats only track locations while parsing, so this
+    /// parses `source` again and stops with an error at the item, whose location is the one
+    /// we are looking for.
+    fn line_of(self, source: &str, item: Item) -> Option<usize> {
+        let seed = FindItem(item);
+        match self {
+            Format::Toml => {
+                let error = seed.deserialize(toml::Deserializer::new(source)).err()?;
+                let offset = error.span()?.start;
+                Some(source[..offset].matches('\n').count() + 1)
+            }
+            Format::Json => {
+                let mut deserializer = serde_json::Deserializer::from_str(source);
+                let error = seed.deserialize(&mut deserializer).err()?;
+                (error.line() > 0).then_some(error.line())
+            }
+            Format::Yaml => {
+                let error = seed
+                    .deserialize(serde_yaml::Deserializer::from_str(source))
+                    .err()?;
+                error.location().map(|location| location.line())
+            }
+        }
+    }
+}
+
+/// Part of the description that failed to build.
+#[derive(Clone, Copy)]
+enum Item {
+    Background,
+    /// Index into `objects`.
+    Object(usize),
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Item::Background => write!(f, "background"),
+            Item::Object(index) => write!(f, "object {}", index + 1),
+        }
+    }
+}
+
+/// Skips through the description up to an item and fails there.
+#[derive(Clone, Copy)]
+struct FindItem(Item);
+
+impl<'de> DeserializeSeed<'de> for FindItem {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for FindItem {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a scene description")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            match (key.as_str(), self.0) {
+                ("background", Item::Background) => map.next_value_seed(Stop)?,
+                ("objects", Item::Object(_)) => map.next_value_seed(self)?,
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        if let Item::Object(index) = self.0 {
+            for _ in 0..index {
+                seq.next_element::<IgnoredAny>()?;
+            }
+            seq.next_element_seed(Stop)?;
+        }
+        Ok(())
+    }
+}
+
+/// Fails on any table. The error is raised by the deserializer itself, before it reads into
+/// the table, so that it carries the location where the table starts.
+struct Stop;
+
+impl<'de> DeserializeSeed<'de> for Stop {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_unit(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Stop {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("nothing")
+    }
+}
+
+/// Number greater than zero.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(try_from = "f64")]
+struct Positive(f64);
+
+impl TryFrom<f64> for Positive {
+    type Error = String;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        if value > 0.0 {
+            Ok(Self(value))
+        } else {
+            Err(format!("expected a positive number, found {}", value))
+        }
+    }
+}
+
+/// Number greater than or equal to zero.
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(try_from = "f64")]
+struct NonNegative(f64);
+
+impl TryFrom<f64> for NonNegative {
+    type Error = String;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        if value >= 0.0 {
+            Ok(Self(value))
+        } else {
+            Err(format!("expected a non-negative number, found {}", value))
+        }
+    }
+}
+
+/// Number in `[0, 1]`.
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(try_from = "f64")]
+struct UnitInterval(f64);
+
+impl TryFrom<f64> for UnitInterval {
+    type Error = String;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        if (0.0..=1.0).contains(&value) {
+            Ok(Self(value))
+        } else {
+            Err(format!(
+                "expected a number between 0 and 1, found {}",
+                value
+            ))
+        }
+    }
+}
+
+/// Vertical field of view in degrees.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(try_from = "f64")]
+struct FieldOfView(f64);
+
+impl TryFrom<f64> for FieldOfView {
+    type Error = String;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        if value > 0.0 && value < 180.0 {
+            Ok(Self(value))
+        } else {
+            Err(format!(
+                "expected a field of view between 0 and 180 degrees, found {}",
+                value
+            ))
+        }
+    }
+}
+
+/// Image size in pixels, positive and small enough for the renderer's `i32`.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(try_from = "i64")]
+struct Pixels(i32);
+
+impl TryFrom<i64> for Pixels {
+    type Error = String;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        match i32::try_from(value) {
+            Ok(pixels) if pixels > 0 => Ok(Self(pixels)),
+            _ => Err(format!(
+                "expected a number of pixels between 1 and {}, found {}",
+                i32::MAX,
+                value
+            )),
+        }
+    }
+}
+
+/// Bounce limit, non-negative and small enough for the renderer's `i32`.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(try_from = "i64")]
+struct Depth(i32);
+
+impl TryFrom<i64> for Depth {
+    type Error = String;
+
+    fn try_from(value: i64) -> Result<Self, Self::Error> {
+        match i32::try_from(value) {
+            Ok(depth) if depth >= 0 => Ok(Self(depth)),
+            _ => Err(format!(
+                "expected a depth between 0 and {}, found {}",
+                i32::MAX,
+                value
+            )),
+        }
+    }
+}
+
+/// Shutter `[open, close]` times, with `open` not after `close`.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(try_from = "[f64; 2]")]
+struct Shutter([f64; 2]);
+
+impl TryFrom<[f64; 2]> for Shutter {
+    type Error = String;
+
+    fn try_from(times: [f64; 2]) -> Result<Self, Self::Error> {
+        if times[0] <= times[1] {
+            Ok(Self(times))
+        } else {
+            Err(format!(
+                "shutter opens at {} after closing at {}",
+                times[0], times[1]
+            ))
+        }
+    }
+}
+
+/// Henyey–Greenstein asymmetry parameter.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(try_from = "f64")]
+struct Asymmetry(f64);
+
+impl TryFrom<f64> for Asymmetry {
+    type Error = String;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        if value > -1.0 && value < 1.0 {
+            Ok(Self(value))
+        } else {
+            Err(format!(
+                "expected a number between -1 and 1, found {}",
+                value
+            ))
+        }
+    }
+}
+
+/// Scaling with non-zero factors.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(try_from = "[f64; 3]")]
+struct Scaling(Transform);
+
+impl TryFrom<[f64; 3]> for Scaling {
+    type Error = String;
+
+    fn try_from(factors: [f64; 3]) -> Result<Self, Self::Error> {
+        if factors.contains(&0.0) {
+            return Err("scale factors must not be zero".to_string());
+        }
+        Ok(Self(Transform::scaling(vec3(factors))))
+    }
+}
+
+/// Transform given as a row-major matrix.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(try_from = "[[f64; 4]; 4]")]
+struct Matrix(Transform);
+
+impl TryFrom<[[f64; 4]; 4]> for Matrix {
+    type Error = String;
+
+    fn try_from(matrix: [[f64; 4]; 4]) -> Result<Self, Self::Error> {
+        Transform::from_matrix(matrix)
+            .map(Self)
+            .ok_or_else(|| "matrix is not invertible".to_string())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDescription {
+    camera: CameraDescription,
+    renderer: RendererDescription,
+    #[serde(default)]
+    background: Option<BackgroundDescription>,
+    #[serde(default)]
+    objects: Vec<ObjectDescription>,
+    #[serde(default)]
+    lights: Vec<LightDescription>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraDescription {
+    look_from: [f64; 3],
+    look_at: [f64; 3],
+    #[serde(default = "default_vup")]
+    vup: [f64; 3],
+    vfov: FieldOfView,
+    /// Defaults to the image aspect ratio.
+    #[serde(default)]
+    aspect_ratio: Option<Positive>,
+    #[serde(default)]
+    aperture: NonNegative,
+    /// Defaults to the distance between `look_from` and `look_at`.
+    #[serde(default)]
+    focus_dist: Option<Positive>,
+    #[serde(default)]
+    shutter: Option<Shutter>,
+}
+
+fn default_vup() -> [f64; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RendererDescription {
+    width: Pixels,
+    height: Pixels,
+    #[serde(default = "default_samples_per_pixel")]
+    samples_per_pixel: NonZeroU32,
+    #[serde(default = "default_max_depth")]
+    max_depth: Depth,
+    #[serde(default)]
+    seed: u64,
+    #[serde(default)]
+    sampler: SamplerDescription,
+    #[serde(default)]
+    tile_size: Option<NonZeroUsize>,
+    #[serde(default)]
+    adaptive: Option<AdaptiveDescription>,
+}
+
+fn default_samples_per_pixel() -> NonZeroU32 {
+    NonZeroU32::new(100).unwrap()
+}
+
+fn default_max_depth() -> Depth {
+    Depth(50)
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+enum SamplerDescription {
+    #[default]
+    Independent,
+    Stratified,
+    Halton,
+    Sobol,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AdaptiveDescription {
+    threshold: Positive,
+    #[serde(default)]
+    pass_samples: Option<NonZeroU32>,
+    #[serde(default)]
+    max_samples: Option<NonZeroU32>,
+    /// In seconds.
+    #[serde(default)]
+    time_budget: Option<Positive>,
+}
+
+#[derive(Deserialize)]
+#[serde(remote = "Self", rename_all = "snake_case", deny_unknown_fields)]
+enum BackgroundDescription {
+    Solid { color: [f64; 3] },
+    Gradient { bottom: [f64; 3], top: [f64; 3] },
+    Environment { path: PathBuf },
+}
+
+#[derive(Deserialize)]
+#[serde(remote = "Self", rename_all = "snake_case", deny_unknown_fields)]
+enum LightDescription {
+    Point {
+        position: [f64; 3],
+        intensity: [f64; 3],
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ObjectDescription {
+    shape: ShapeDescription,
+    /// Defaults to gray diffuse; for OBJ files, overrides the materials from the file.
+    #[serde(default)]
+    material: Option<MaterialDescription>,
+    #[serde(default)]
+    transform: Vec<TransformDescription>,
+}
+
+#[derive(Deserialize)]
+#[serde(remote = "Self", rename_all = "snake_case", deny_unknown_fields)]
+enum ShapeDescription {
+    Sphere {
+        center: [f64; 3],
+        radius: Positive,
+    },
+    MovingSphere {
+        center0: [f64; 3],
+        center1: [f64; 3],
+        #[serde(default)]
+        time0: f64,
+        #[serde(default = "default_time1")]
+        time1: f64,
+        radius: Positive,
+    },
+    Triangle {
+        vertices: [[f64; 3]; 3],
+        #[serde(default)]
+        normals: Option<[[f64; 3]; 3]>,
+    },
+    Quad {
+        origin: [f64; 3],
+        u: [f64; 3],
+        v: [f64; 3],
+    },
+    Box {
+        min: [f64; 3],
+        max: [f64; 3],
+    },
+    Obj {
+        path: PathBuf,
+    },
+    ConstantMedium {
+        boundary: Box<ShapeDescription>,
+        density: NonNegative,
+    },
+    GridMedium {
+        path: PathBuf,
+        min: [f64; 3],
+        max: [f64; 3],
+        #[serde(default = "default_density")]
+        density: NonNegative,
+    },
+}
+
+fn default_time1() -> f64 {
+    1.0
+}
+
+fn default_density() -> NonNegative {
+    NonNegative(1.0)
+}
+
+/// One step of an object's transform, written as a table with a single key such as
+/// `{ translate = [1, 0, 0] }`.
+#[derive(Deserialize)]
+#[serde(try_from = "TransformStep")]
+struct TransformDescription(Transform);
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TransformStep {
+    #[serde(default)]
+    translate: Option<[f64; 3]>,
+    #[serde(default)]
+    scale: Option<Scaling>,
+    #[serde(default)]
+    rotate: Option<Rotation>,
+    #[serde(default)]
+    rotate_x: Option<f64>,
+    #[serde(default)]
+    rotate_y: Option<f64>,
+    #[serde(default)]
+    rotate_z: Option<f64>,
+    #[serde(default)]
+    matrix: Option<Matrix>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Rotation {
+    axis: [f64; 3],
+    degrees: f64,
+}
+
+impl TryFrom<TransformStep> for TransformDescription {
+    type Error = String;
+
+    fn try_from(step: TransformStep) -> Result<Self, Self::Error> {
+        if let Some(Rotation {
+            axis: [0.0, 0.0, 0.0],
+            ..
+        }) = step.rotate
+        {
+            return Err("rotation axis must not be zero".to_string());
+        }
+
+        let transforms = [
+            step.translate
+                .map(|offset| Transform::translation(vec3(offset))),
+            step.scale.map(|scaling| scaling.0),
+            step.rotate
+                .map(|rotation| Transform::rotation(vec3(rotation.axis), rotation.degrees)),
+            step.rotate_x.map(Transform::rotation_x),
+            step.rotate_y.map(Transform::rotation_y),
+            step.rotate_z.map(Transform::rotation_z),
+            step.matrix.map(|matrix| matrix.0),
+        ];
+        let mut transforms = transforms.into_iter().flatten();
+        match (transforms.next(), transforms.next()) {
+            (Some(transform), None) => Ok(Self(transform)),
+            _ => Err(
+                "a transform step needs exactly one of `translate`, `scale`, `rotate`, \
+                 `rotate_x`, `rotate_y`, `rotate_z` or `matrix`"
+                    .to_string(),
+            ),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(remote = "Self", rename_all = "snake_case", deny_unknown_fields)]
+enum MaterialDescription {
+    Lambertian {
+        albedo: TextureDescription,
+    },
+    Metal {
+        albedo: TextureDescription,
+        #[serde(default)]
+        fuzz: UnitInterval,
+    },
+    Dielectric {
+        ir: Positive,
+    },
+    DiffuseLight {
+        emit: [NonNegative; 3],
+    },
+    Isotropic {
+        albedo: TextureDescription,
+    },
+    HenyeyGreenstein {
+        albedo: TextureDescription,
+        g: Asymmetry,
+    },
+}
+
+/// Either a plain color or a table describing a pattern.
+enum TextureDescription {
+    Solid([f64; 3]),
+    Pattern(PatternDescription),
+}
+
+#[derive(Deserialize)]
+#[serde(remote = "Self", rename_all = "snake_case", deny_unknown_fields)]
+enum PatternDescription {
+    Checker {
+        scale: Positive,
+        even: [f64; 3],
+        odd: [f64; 3],
+    },
+    Image {
+        path: PathBuf,
+    },
+    Noise {
+        scale: Positive,
+    },
+    Turbulence {
+        scale: Positive,
+        #[serde(default = "default_octaves")]
+        octaves: u32,
+    },
+    Marble {
+        scale: Positive,
+        #[serde(default = "default_octaves")]
+        octaves: u32,
+    },
+}
+
+fn default_octaves() -> u32 {
+    7
+}
+
+impl<'de> Deserialize<'de> for TextureDescription {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TextureVisitor;
+
+        impl<'de> Visitor<'de> for TextureVisitor {
+            type Value = TextureDescription;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a color or a texture table")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+                let color = Deserialize::deserialize(SeqAccessDeserializer::new(seq))?;
+                Ok(TextureDescription::Solid(color))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                let pattern = Deserialize::deserialize(MapAccessDeserializer::new(map))?;
+                Ok(TextureDescription::Pattern(pattern))
+            }
+        }
+
+        deserializer.deserialize_any(TextureVisitor)
+    }
+}
+
+/// Enum written as a table whose `type` key picks the variant and whose other keys are the
+/// variant's fields.
+///
+/// Serde's internally tagged enums buffer the whole table before looking at it, so errors in
+/// the fields point at the table. These enums instead derive an externally tagged
+/// `Deserialize` with `#[serde(remote = "Self")]`, and [`TaggedVisitor`] hands it the rest of
+/// the table after reading the `type`, so field errors keep their location. This needs the
+/// `type` to be the first key of the table.
+trait Tagged: Sized {
+    fn deserialize_variant<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error>;
+}
+
+macro_rules! tagged {
+    ($($name:ident),*) => {$(
+        impl Tagged for $name {
+            fn deserialize_variant<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Self, D::Error> {
+                // The inherent function generated by the remote derive
+                $name::deserialize(deserializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_map(TaggedVisitor(PhantomData))
+            }
+        }
+    )*};
+}
+
+tagged!(
+    BackgroundDescription,
+    LightDescription,
+    ShapeDescription,
+    MaterialDescription,
+    PatternDescription
+);
+
+struct TaggedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Tagged> Visitor<'de> for TaggedVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a table with a `type`")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        match map.next_key::<String>()? {
+            Some(key) if key == "type" => {
+                let variant = map.next_value()?;
+                T::deserialize_variant(VariantTable { variant, map })
+            }
+            Some(key) => Err(de::Error::custom(format!(
+                "expected `type` as the first key of the table, found `{}`",
+                key
+            ))),
+            None => Err(de::Error::missing_field("type")),
+        }
+    }
+}
+
+/// Rest of a tagged table, presented as the externally tagged enum variant `variant`.
+struct VariantTable<A> {
+    variant: String,
+    map: A,
+}
+
+impl<'de, A: MapAccess<'de>> Deserializer<'de> for VariantTable<A> {
+    type Error = A::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        option unit unit_struct newtype_struct seq tuple tuple_struct map struct enum
+        identifier ignored_any
+    }
+}
+
+impl<'de, A: MapAccess<'de>> EnumAccess<'de> for VariantTable<A> {
+    type Error = A::Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let variant: StrDeserializer<A::Error> = self.variant.as_str().into_deserializer();
+        let value = seed.deserialize(variant)?;
+        Ok((value, self))
+    }
+}
+
+impl<'de, A: MapAccess<'de>> VariantAccess<'de> for VariantTable<A> {
+    type Error = A::Error;
+
+    fn unit_variant(mut self) -> Result<(), Self::Error> {
+        match self.map.next_key::<String>()? {
+            Some(field) => Err(de::Error::unknown_field(&field, &[])),
+            None => Ok(()),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        seed.deserialize(MapAccessDeserializer::new(self.map))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        Err(de::Error::invalid_type(Unexpected::Map, &visitor))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_map(self.map)
+    }
+}
+
+/// Names the file a referenced file failed to load from. Decoding errors already do.
+fn file_error(path: &Path, e: io::Error) -> io::Error {
+    if e.kind() == ErrorKind::InvalidData {
+        e
+    } else {
+        io::Error::new(e.kind(), format!("{}: {}", path.display(), e))
+    }
+}
+
+fn vec3(v: [f64; 3]) -> Vec3 {
+    Vec3::new(v[0], v[1], v[2])
+}
+
+/// Files referenced by the description, each loaded only once.
+struct Resources<'a> {
+    base_dir: &'a Path,
+    images: HashMap<PathBuf, Arc<ImageTexture>>,
+    grids: HashMap<PathBuf, Arc<VoxelGrid>>,
+}
+
+impl SceneDescription {
+    /// Builds the scene, or fails with the item that could not be built.
+    fn build(self, base_dir: &Path) -> Result<(Scene, Camera, Renderer), (Item, io::Error)> {
+        let mut resources = Resources {
+            base_dir,
+            images: HashMap::new(),
+            grids: HashMap::new(),
+        };
+
+        let mut scene = Scene::default();
+        if let Some(background) = self.background {
+            let background = resources
+                .background(background)
+                .map_err(|e| (Item::Background, e))?;
+            scene.set_background(background);
+        }
+        for (index, object) in self.objects.into_iter().enumerate() {
+            let objects = resources
+                .object(object)
+                .map_err(|e| (Item::Object(index), e))?;
+            for (shape, material) in objects {
+                scene.add(shape, material);
+            }
+        }
+        for light in self.lights {
+            match light {
+                LightDescription::Point {
+                    position,
+                    intensity,
+                } => scene.add_point_light(vec3(position), vec3(intensity)),
+            }
+        }
+
+        let renderer = self.renderer.build();
+        let camera = self.camera.build(&self.renderer);
+        Ok((scene, camera, renderer))
+    }
+}
+
+impl CameraDescription {
+    fn build(&self, renderer: &RendererDescription) -> Camera {
+        let look_from = vec3(self.look_from);
+        let look_at = vec3(self.look_at);
+        let aspect_ratio = self.aspect_ratio.map_or(
+            renderer.width.0 as f64 / renderer.height.0 as f64,
+            |ratio| ratio.0,
+        );
+        let focus_dist = self
+            .focus_dist
+            .map_or((look_from - look_at).len(), |dist| dist.0);
+
+        let camera = Camera::new(
+            look_from,
+            look_at,
+            vec3(self.vup),
+            self.vfov.0,
+            aspect_ratio,
+            self.aperture.0,
+            focus_dist,
+        );
+        match self.shutter {
+            Some(Shutter([open, close])) => camera.with_shutter(open, close),
+            None => camera,
+        }
+    }
+}
+
+impl RendererDescription {
+    fn build(&self) -> Renderer {
+        let mut renderer = Renderer::new(
+            self.width.0,
+            self.height.0,
+            self.samples_per_pixel.get(),
+            self.max_depth.0,
+        );
+        renderer.set_seed(self.seed);
+        renderer.set_sampler(match self.sampler {
+            SamplerDescription::Independent => SamplerKind::Independent,
+            SamplerDescription::Stratified => SamplerKind::Stratified,
+            SamplerDescription::Halton => SamplerKind::Halton,
+            SamplerDescription::Sobol => SamplerKind::Sobol,
+        });
+        if let Some(tile_size) = self.tile_size {
+            renderer.set_tile_size(tile_size.get());
+        }
+        if let Some(adaptive) = &self.adaptive {
+            let mut sampling = AdaptiveSampling::new(adaptive.threshold.0);
+            if let Some(pass_samples) = adaptive.pass_samples {
+                sampling = sampling.with_pass_samples(pass_samples.get());
+            }
+            if let Some(max_samples) = adaptive.max_samples {
+                sampling = sampling.with_max_samples(max_samples.get());
+            }
+            if let Some(time_budget) = adaptive.time_budget {
+                sampling = sampling.with_time_budget(Duration::from_secs_f64(time_budget.0));
+            }
+            renderer.set_adaptive_sampling(sampling);
+        }
+        renderer
+    }
+}
+
+impl Resources<'_> {
+    fn image(&mut self, path: &Path) -> io::Result<Arc<ImageTexture>> {
+        let path = self.base_dir.join(path);
+        if let Some(image) = self.images.get(&path) {
+            return Ok(Arc::clone(image));
+        }
+        let image = Arc::new(ImageTexture::load(&path).map_err(|e| file_error(&path, e))?);
+        self.images.insert(path, Arc::clone(&image));
+        Ok(image)
+    }
+
+    fn grid(&mut self, path: &Path) -> io::Result<Arc<VoxelGrid>> {
+        let path = self.base_dir.join(path);
+        if let Some(grid) = self.grids.get(&path) {
+            return Ok(Arc::clone(grid));
+        }
+        let grid = Arc::new(VoxelGrid::load(&path).map_err(|e| file_error(&path, e))?);
+        self.grids.insert(path, Arc::clone(&grid));
+        Ok(grid)
+    }
+
+    fn background(&mut self, background: BackgroundDescription) -> io::Result<Background> {
+        Ok(match background {
+            BackgroundDescription::Solid { color } => Background::Solid { color: vec3(color) },
+            BackgroundDescription::Gradient { bottom, top } => Background::Gradient {
+                bottom: vec3(bottom),
+                top: vec3(top),
+            },
+            BackgroundDescription::Environment { path } => {
+                let path = self.base_dir.join(path);
+                let map = EnvironmentMap::load(&path).map_err(|e| file_error(&path, e))?;
+                Background::Environment { map: Arc::new(map) }
+            }
+        })
+    }
+
+    fn object(&mut self, object: ObjectDescription) -> io::Result<Vec<(Shape, Material)>> {
+        let material = object
+            .material
+            .map(|material| self.material(material))
+            .transpose()?;
+        let shapes = self.shape(object.shape)?;
+
+        let transform = object
+            .transform
+            .iter()
+            .fold(Transform::identity(), |transform, step| {
+                transform.then(&step.0)
+            });
+        let transform = (!object.transform.is_empty()).then(|| Arc::new(transform));
+
+        Ok(shapes
+            .into_iter()
+            .map(|(shape, shape_material)| {
+                let shape = match &transform {
+                    Some(transform) => Shape::Instance {
+                        shape: Arc::new(shape),
+                        transform: Arc::clone(transform),
+                    },
+                    None => shape,
+                };
+                let material =
+                    material
+                        .clone()
+                        .or(shape_material)
+                        .unwrap_or_else(|| Material::Lambertian {
+                            albedo: Color::new(0.5, 0.5, 0.5).into(),
+                        });
+                (shape, material)
+            })
+            .collect())
+    }
+
+    /// Shapes described by `shape`, together with the materials they come with, if any.
+    fn shape(&mut self, shape: ShapeDescription) -> io::Result<Vec<(Shape, Option<Material>)>> {
+        let shape = match shape {
+            ShapeDescription::Sphere { center, radius } => Shape::Sphere {
+                center: vec3(center),
+                radius: radius.0,
+            },
+            ShapeDescription::MovingSphere {
+                center0,
+                center1,
+                time0,
+                time1,
+                radius,
+            } => {
+                // Two separate fields, so this can't be checked while deserializing
+                if time0 > time1 {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "moving sphere starts at {} after ending at {}",
+                            time0, time1
+                        ),
+                    ));
+                }
+                Shape::MovingSphere {
+                    center0: vec3(center0),
+                    center1: vec3(center1),
+                    time0,
+                    time1,
+                    radius: radius.0,
+                }
+            }
+            ShapeDescription::Triangle { vertices, normals } => Shape::Triangle {
+                vertices: vertices.map(vec3),
+                normals: normals.map(|normals| normals.map(|n| Vec3::unit_vector(&vec3(n)))),
+            },
+            ShapeDescription::Quad { origin, u, v } => Shape::Quad {
+                origin: vec3(origin),
+                u: vec3(u),
+                v: vec3(v),
+            },
+            ShapeDescription::Box { min, max } => Shape::Box {
+                min: vec3(min),
+                max: vec3(max),
+            },
+            ShapeDescription::Obj { path } => {
+                let path = self.base_dir.join(path);
+                let objects = load_obj(&path).map_err(|e| file_error(&path, e))?;
+                return Ok(objects
+                    .into_iter()
+                    .map(|(shape, material)| (shape, Some(material)))
+                    .collect());
+            }
+            ShapeDescription::ConstantMedium { boundary, density } => {
+                let mut boundary = self.shape(*boundary)?;
+                if boundary.len() != 1 {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        "medium boundary must be a single shape",
+                    ));
+                }
+                Shape::ConstantMedium {
+                    boundary: Arc::new(boundary.remove(0).0),
+                    density: density.0,
+                }
+            }
+            ShapeDescription::GridMedium {
+                path,
+                min,
+                max,
+                density,
+            } => {
+                if (0..3).any(|axis| min[axis] == max[axis]) {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        "grid medium box must not be flat",
+                    ));
+                }
+                Shape::GridMedium {
+                    grid: self.grid(&path)?,
+                    min: vec3(min),
+                    max: vec3(max),
+                    density: density.0,
+                }
+            }
+        };
+        Ok(vec![(shape, None)])
+    }
+
+    fn material(&mut self, material: MaterialDescription) -> io::Result<Material> {
+        Ok(match material {
+            MaterialDescription::Lambertian { albedo } => Material::Lambertian {
+                albedo: self.texture(albedo)?,
+            },
+            MaterialDescription::Metal { albedo, fuzz } => Material::Metal {
+                albedo: self.texture(albedo)?,
+                fuzz: fuzz.0,
+            },
+            MaterialDescription::Dielectric { ir } => Material::Dielectric { ir: ir.0 },
+            MaterialDescription::DiffuseLight { emit } => Material::DiffuseLight {
+                emit: vec3(emit.map(|c| c.0)),
+            },
+            MaterialDescription::Isotropic { albedo } => Material::Isotropic {
+                albedo: self.texture(albedo)?,
+            },
+            MaterialDescription::HenyeyGreenstein { albedo, g } => Material::HenyeyGreenstein {
+                albedo: self.texture(albedo)?,
+                g: g.0,
+            },
+        })
+    }
+
+    fn texture(&mut self, texture: TextureDescription) -> io::Result<Texture> {
+        let pattern = match texture {
+            TextureDescription::Solid(color) => return Ok(vec3(color).into()),
+            TextureDescription::Pattern(pattern) => pattern,
+        };
+        Ok(match pattern {
+            PatternDescription::Checker { scale, even, odd } => Texture::Checker {
+                scale: scale.0,
+                even: vec3(even),
+                odd: vec3(odd),
+            },
+            PatternDescription::Image { path } => Texture::Image {
+                image: self.image(&path)?,
+            },
+            PatternDescription::Noise { scale } => Texture::Noise { scale: scale.0 },
+            PatternDescription::Turbulence { scale, octaves } => Texture::Turbulence {
+                scale: scale.0,
+                octaves,
+            },
+            PatternDescription::Marble { scale, octaves } => Texture::Marble {
+                scale: scale.0,
+                octaves,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Writes `source` to a fresh directory as `scene.<extension>` and loads it.
+    fn load(extension: &str, source: &str) -> io::Result<(Scene, Camera, Renderer)> {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "raytracer-scene-{}-{}",
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("scene.{}", extension));
+        fs::write(&path, source)?;
+        let result = load_scene(&path);
+        fs::remove_dir_all(&dir)?;
+        result
+    }
+
+    fn error(extension: &str, source: &str) -> String {
+        match load(extension, source) {
+            Ok(_) => panic!("expected the description to be rejected"),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    const TOML: &str = r#"
+[camera]
+look_from = [0, 0, 5]
+look_at = [0, 0, 0]
+vfov = 40
+
+[renderer]
+width = 16
+height = 9
+
+[[objects]]
+shape = { type = "sphere", center = [0, 0, 0], radius = 1 }
+material = { type = "lambertian", albedo = { type = "checker", scale = 0.5, even = [1, 1, 1], odd = [0, 0, 0] } }
+
+[[objects]]
+[objects.shape]
+type = "sphere"
+radius = 0.5
+center = [0, 2, 0]
+[objects.material]
+type = "diffuse_light"
+emit = [4, 4, 4]
+
+[[lights]]
+type = "point"
+position = [0, 5, 0]
+intensity = [10, 10, 10]
+"#;
+
+    const JSON: &str = r#"{
+  "camera": { "look_from": [0, 0, 5], "look_at": [0, 0, 0], "vfov": 40 },
+  "renderer": { "width": 16, "height": 9 },
+  "objects": [
+    {
+      "shape": { "type": "sphere", "center": [0, 0, 0], "radius": 1 },
+      "material": { "type": "metal", "albedo": [0.8, 0.8, 0.8], "fuzz": 0.1 },
+      "transform": [{ "scale": [1, 2, 1] }, { "rotate_y": 30 }]
+    },
+    {
+      "shape": { "type": "sphere", "center": [0, 2, 0], "radius": 0.5 },
+      "material": { "type": "diffuse_light", "emit": [4, 4, 4] }
+    }
+  ],
+  "lights": [{ "type": "point", "position": [0, 5, 0], "intensity": [10, 10, 10] }]
+}
+"#;
+
+    const YAML: &str = r#"
+camera:
+  look_from: [0, 0, 5]
+  look_at: [0, 0, 0]
+  vfov: 40
+renderer:
+  width: 16
+  height: 9
+objects:
+  - shape: { type: sphere, center: [0, 0, 0], radius: 1 }
+    material: { type: dielectric, ir: 1.5 }
+  - shape:
+      type: sphere
+      center: [0, 2, 0]
+      radius: 0.5
+    material:
+      type: diffuse_light
+      emit: [4, 4, 4]
+    transform: [{ translate: [0, 1, 0] }]
+lights:
+  - { type: point, position: [0, 5, 0], intensity: [10, 10, 10] }
+"#;
+
+    fn check_valid(extension: &str, source: &str) {
+        let (scene, _, _) = load(extension, source).unwrap();
+        // The emissive sphere and the point light
+        assert_eq!(scene.lights().len(), 2);
+    }
+
+    #[test]
+    fn loads_valid_descriptions() {
+        check_valid("toml", TOML);
+        check_valid("json", JSON);
+        check_valid("yaml", YAML);
+    }
+
+    #[test]
+    fn reports_invalid_toml() {
+        let source = TOML.replace("center = [0, 2, 0]", "center = [0, 2]");
+        let message = error("toml", &source);
+        assert!(message.contains("at line 19"), "{}", message);
+
+        let source = TOML.replace("radius = 1 }", r#"radius = 1, path = "a.obj" }"#);
+        let message = error("toml", &source);
+        assert!(message.contains("at line 12"), "{}", message);
+        assert!(message.contains("unknown field `path`"), "{}", message);
+
+        let source = TOML
+            .replace(
+                "radius = 0.5\ncenter = [0, 2, 0]",
+                r#"path = "missing.obj""#,
+            )
+            .replace(
+                r#"type = "sphere"
+path"#,
+                r#"type = "obj"
+path"#,
+            );
+        let message = error("toml", &source);
+        assert!(message.contains("scene.toml:15: object 2: "), "{}", message);
+        assert!(message.contains("missing.obj"), "{}", message);
+    }
+
+    #[test]
+    fn rejects_out_of_range_values() {
+        let source = TOML.replace("width = 16", "width = 3000000000");
+        let message = error("toml", &source);
+        assert!(message.contains("at line 8"), "{}", message);
+        assert!(message.contains("found 3000000000"), "{}", message);
+
+        let source = JSON.replace(r#""emit": [4, 4, 4]"#, r#""emit": [4, -4, 4]"#);
+        let message = error("json", &source);
+        assert!(message.contains("at line 12"), "{}", message);
+        assert!(
+            message.contains("non-negative number, found -4"),
+            "{}",
+            message
+        );
+
+        let source = YAML.replace("  vfov: 40", "  vfov: 40\n  shutter: [1, 0]");
+        let message = error("yaml", &source);
+        assert!(
+            message.contains("camera: shutter opens at 1 after closing at 0"),
+            "{}",
+            message
+        );
+
+        let source = TOML.replace(
+            r#"{ type = "sphere", center = [0, 0, 0], radius = 1 }"#,
+            r#"{ type = "moving_sphere", center0 = [0, 0, 0], center1 = [0, 1, 0], time0 = 2, radius = 1 }"#,
+        );
+        let message = error("toml", &source);
+        assert!(message.contains("scene.toml:11: object 1: "), "{}", message);
+        assert!(
+            message.contains("starts at 2 after ending at 1"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn requires_type_first() {
+        let source = TOML.replace(
+            r#"{ type = "checker", scale = 0.5,"#,
+            r#"{ scale = -0.5, type = "checker","#,
+        );
+        let message = error("toml", &source);
+        assert!(message.contains("at line 13"), "{}", message);
+        assert!(
+            message.contains("expected `type` as the first key of the table, found `scale`"),
+            "{}",
+            message
+        );
+
+        let source = YAML.replace(
+            "type: sphere\n      center: [0, 2, 0]\n      radius: 0.5",
+            "center: [0, 2, 0]\n      radius: -0.5\n      type: sphere",
+        );
+        let message = error("yaml", &source);
+        assert!(message.contains("at line 13"), "{}", message);
+        assert!(message.contains("found `center`"), "{}", message);
+    }
+
+    #[test]
+    fn reports_invalid_json() {
+        let source = JSON.replace(r#""radius": 0.5"#, r#""radius": -0.5, "extra": 1"#);
+        let message = error("json", &source);
+        assert!(message.contains("at line 11"), "{}", message);
+        assert!(message.contains("positive number"), "{}", message);
+
+        let source = JSON.replace(
+            r#"{ "type": "sphere", "center": [0, 2, 0], "radius": 0.5 }"#,
+            r#"{ "type": "obj", "path": "missing.obj" }"#,
+        );
+        let message = error("json", &source);
+        assert!(message.contains("scene.json:10: object 2: "), "{}", message);
+        assert!(message.contains("missing.obj"), "{}", message);
+    }
+
+    #[test]
+    fn reports_invalid_yaml() {
+        let source = YAML.replace("type: diffuse_light", "type: glow");
+        let message = error("yaml", &source);
+        assert!(message.contains("at line 17"), "{}", message);
+        assert!(message.contains("unknown variant `glow`"), "{}", message);
+
+        let source = YAML.replace(
+            "type: sphere\n      center: [0, 2, 0]\n      radius: 0.5",
+            "type: obj\n      path: missing.obj",
+        );
+        let message = error("yaml", &source);
+        assert!(message.contains("scene.yaml:12: object 2: "), "{}", message);
+        assert!(message.contains("missing.obj"), "{}", message);
+    }
+}
@@ -0,0 +1,85 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+/// Reads a dense voxel grid. The file starts with the text line `VOXELS <nx> <ny> <nz>`,
+/// followed by `nx * ny * nz` little endian 32 bit floats with x varying fastest, then y,
+/// then z.
+///
+/// Returns the grid resolution and the voxel values.
+pub fn load_voxels<P: AsRef<Path>>(path: P) -> io::Result<([usize; 3], Vec<f32>)> {
+    let path = path.as_ref();
+    let data = fs::read(path)?;
+    decode(&data).map_err(|message| {
+        io::Error::new(
+            ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), message),
+        )
+    })
+}
+
+fn decode(data: &[u8]) -> Result<([usize; 3], Vec<f32>), String> {
+    let len = data
+        .iter()
+        .position(|&b| b == b'\n')
+        .ok_or_else(|| "unexpected end of header".to_string())?;
+    let header = String::from_utf8_lossy(&data[..len]).into_owned();
+
+    let fields: Vec<_> = header.split_whitespace().collect();
+    let resolution = match fields.as_slice() {
+        ["VOXELS", nx, ny, nz] => [
+            nx.parse::<usize>().map_err(|e| e.to_string())?,
+            ny.parse::<usize>().map_err(|e| e.to_string())?,
+            nz.parse::<usize>().map_err(|e| e.to_string())?,
+        ],
+        _ => return Err(format!("invalid voxel grid header '{}'", header)),
+    };
+
+    let size = resolution
+        .iter()
+        .try_fold(4usize, |size, &n| size.checked_mul(n))
+        .ok_or_else(|| "voxel grid too large".to_string())?;
+    let body = &data[len + 1..];
+    if body.len() != size {
+        return Err(format!(
+            "expected {} bytes of voxel data, found {}",
+            size,
+            body.len()
+        ));
+    }
+
+    let values = body
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect();
+    Ok((resolution, values))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(resolution: [usize; 3], values: &[f32]) -> Vec<u8> {
+        let [nx, ny, nz] = resolution;
+        let mut data = format!("VOXELS {} {} {}\n", nx, ny, nz).into_bytes();
+        for v in values {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn decodes_values() {
+        let (resolution, values) = decode(&encode([2, 1, 1], &[0.0, 0.5])).unwrap();
+        assert_eq!(resolution, [2, 1, 1]);
+        assert_eq!(values, [0.0, 0.5]);
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        let error = decode(&encode([2, 2, 1], &[0.0, 0.5])).unwrap_err();
+        assert_eq!(error, "expected 16 bytes of voxel data, found 8");
+    }
+}
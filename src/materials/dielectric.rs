@@ -1,8 +1,6 @@
-use rand::Rng;
+use crate::{render::sampler::Sampler, Color, Vec3, shapes::HitRecord};
 
-use crate::{ray::Ray, Color, Vec3, shapes::HitRecord};
-
-use super::material::Scatter;
+use super::material::{BsdfSample, Scatter};
 
 pub struct Dielectric {
     ir: f64,
@@ -22,32 +20,26 @@ impl Dielectric {
 }
 
 impl Scatter for Dielectric {
-    fn scatter(
-        &self,
-        r: &crate::ray::Ray,
-        rec: &HitRecord,
-    ) -> Option<(Ray, Color)> {
+    fn sample(&self, wo: &Vec3, rec: &HitRecord, sampler: &mut dyn Sampler) -> Option<BsdfSample> {
         let refraction_ratio = if rec.front_face() {
             1.0 / self.ir
         } else {
             self.ir
         };
-        let unit_direction = Vec3::unit_vector(r.dir());
+        let unit_direction = -Vec3::unit_vector(wo);
 
         let cos_theta = Vec3::dot(&-unit_direction, rec.normal()).min(1.0);
         let sin_theta = (1.0 - cos_theta.powf(2.0)).sqrt();
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
 
-        let mut rng = rand::thread_rng();
-
         let direction = if cannot_refract
-            || Dielectric::reflectance(cos_theta, refraction_ratio) > rng.gen_range(0.0..1.0)
+            || Dielectric::reflectance(cos_theta, refraction_ratio) > sampler.get_1d()
         {
             Vec3::reflect(&unit_direction, rec.normal())
         } else {
             Vec3::refract(&unit_direction, rec.normal(), refraction_ratio)
         };
 
-        Some((Ray::new(*rec.p(), direction), Color::new(1.0, 1.0, 1.0)))
+        Some(BsdfSample::new(direction, Color::new(1.0, 1.0, 1.0), None))
     }
 }
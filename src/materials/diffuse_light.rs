@@ -0,0 +1,33 @@
+use crate::{render::sampler::Sampler, ray::Ray, shapes::HitRecord, Color, Vec3};
+
+use super::material::{BsdfSample, Scatter};
+
+pub struct DiffuseLight {
+    emit: Color,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: &Color) -> Self {
+        Self { emit: *emit }
+    }
+}
+
+impl Scatter for DiffuseLight {
+    fn sample(
+        &self,
+        _wo: &Vec3,
+        _rec: &HitRecord,
+        _sampler: &mut dyn Sampler,
+    ) -> Option<BsdfSample> {
+        None
+    }
+
+    fn emitted(&self, _r: &Ray, rec: &HitRecord) -> Color {
+        // Lights only emit from their front side
+        if rec.front_face() {
+            self.emit
+        } else {
+            Color::default()
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::f64::consts::PI;
+
+use crate::{render::sampler::Sampler, shapes::HitRecord, Color, Texture, TextureValue, Vec3};
+
+use super::material::{BsdfSample, Scatter};
+
+/// Henyey–Greenstein phase function. Positive `g` scatters light mostly forward, negative
+/// `g` mostly back towards where it came from, and zero is isotropic.
+pub struct HenyeyGreenstein<'a> {
+    albedo: &'a Texture,
+    g: f64,
+}
+
+impl<'a> HenyeyGreenstein<'a> {
+    pub fn new(albedo: &'a Texture, g: f64) -> Self {
+        // |g| = 1 would be a delta distribution
+        Self {
+            albedo,
+            g: g.clamp(-0.999, 0.999),
+        }
+    }
+
+    /// Phase function value for the cosine between `wo` and `wi`, both pointing away from the
+    /// scattering point.
+    fn phase(&self, cos_theta: f64) -> f64 {
+        let g = self.g;
+        let denom = 1.0 + g * g + 2.0 * g * cos_theta;
+        (1.0 - g * g) / (4.0 * PI * denom * denom.max(0.0).sqrt())
+    }
+}
+
+impl Scatter for HenyeyGreenstein<'_> {
+    fn sample(&self, wo: &Vec3, rec: &HitRecord, sampler: &mut dyn Sampler) -> Option<BsdfSample> {
+        let (u1, u2) = sampler.get_2d();
+        let g = self.g;
+        let cos_theta = if g.abs() < 1e-3 {
+            1.0 - 2.0 * u1
+        } else {
+            let s = (1.0 - g * g) / (1.0 + g - 2.0 * g * u1);
+            -(1.0 + g * g - s * s) / (2.0 * g)
+        };
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * u2;
+
+        let (t, b) = Vec3::orthonormal_basis(wo);
+        let wi = sin_theta * phi.cos() * t + sin_theta * phi.sin() * b + cos_theta * *wo;
+
+        // Sampling is exact, so the weight is just the albedo
+        let pdf = self.phase(cos_theta);
+        let albedo = self.albedo.value(rec.uv(), rec.p());
+        Some(BsdfSample::new(wi, albedo, Some(pdf)))
+    }
+
+    fn eval(&self, wi: &Vec3, wo: &Vec3, rec: &HitRecord) -> Color {
+        self.phase(Vec3::dot(wi, wo)) * self.albedo.value(rec.uv(), rec.p())
+    }
+
+    fn pdf(&self, wi: &Vec3, wo: &Vec3, _rec: &HitRecord) -> f64 {
+        self.phase(Vec3::dot(wi, wo))
+    }
+}
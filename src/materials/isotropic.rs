@@ -0,0 +1,35 @@
+use std::f64::consts::PI;
+
+use crate::{render::sampler::Sampler, shapes::HitRecord, Color, Texture, TextureValue, Vec3};
+
+use super::material::{BsdfSample, Scatter};
+
+/// Phase function of a participating medium that scatters light equally in all directions.
+pub struct Isotropic<'a> {
+    albedo: &'a Texture,
+}
+
+impl<'a> Isotropic<'a> {
+    pub fn new(albedo: &'a Texture) -> Self {
+        Self { albedo }
+    }
+}
+
+impl Scatter for Isotropic<'_> {
+    fn sample(&self, wo: &Vec3, rec: &HitRecord, sampler: &mut dyn Sampler) -> Option<BsdfSample> {
+        let (u1, u2) = sampler.get_2d();
+        let wi = Vec3::sample_unit_sphere(u1, u2);
+        let pdf = self.pdf(&wi, wo, rec);
+        let albedo = self.albedo.value(rec.uv(), rec.p());
+        Some(BsdfSample::new(wi, albedo, Some(pdf)))
+    }
+
+    fn eval(&self, _wi: &Vec3, _wo: &Vec3, rec: &HitRecord) -> Color {
+        // There is no surface, so no cosine term either
+        self.albedo.value(rec.uv(), rec.p()) / (4.0 * PI)
+    }
+
+    fn pdf(&self, _wi: &Vec3, _wo: &Vec3, _rec: &HitRecord) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+}
@@ -1,31 +1,43 @@
-use crate::{ray::Ray, Color, Vec3, shapes::HitRecord};
+use std::f64::consts::PI;
 
-use super::material::Scatter;
+use crate::{render::sampler::Sampler, shapes::HitRecord, Color, Texture, TextureValue, Vec3};
 
-pub struct Lambertian {
-    albedo: Color,
+use super::material::{BsdfSample, Scatter};
+
+pub struct Lambertian<'a> {
+    albedo: &'a Texture,
 }
 
-impl Lambertian {
-    pub fn new(albedo: &Color) -> Self {
-        Self { albedo: *albedo }
+impl<'a> Lambertian<'a> {
+    pub fn new(albedo: &'a Texture) -> Self {
+        Self { albedo }
     }
 }
 
-impl Scatter for Lambertian {
-    fn scatter(
-        &self,
-        _r: &crate::ray::Ray,
-        rec: &HitRecord,
-    ) -> Option<(crate::ray::Ray, Color)> {
-        let mut scatter_direction = *rec.normal() + Vec3::random_unit_vector();
+impl Scatter for Lambertian<'_> {
+    fn sample(&self, wo: &Vec3, rec: &HitRecord, sampler: &mut dyn Sampler) -> Option<BsdfSample> {
+        let (u1, u2) = sampler.get_2d();
+        let mut scatter_direction = *rec.normal() + Vec3::sample_unit_sphere(u1, u2);
 
         // Catch degenerate scatter direction
         if scatter_direction.near_zero() {
             scatter_direction = *rec.normal();
         }
 
-        let scattered = Ray::new(*rec.p(), scatter_direction);
-        Some((scattered, self.albedo))
+        // Cosine weighted sampling cancels out everything but the albedo
+        let wi = Vec3::unit_vector(&scatter_direction);
+        let pdf = self.pdf(&wi, wo, rec);
+        let albedo = self.albedo.value(rec.uv(), rec.p());
+        Some(BsdfSample::new(wi, albedo, Some(pdf)))
+    }
+
+    fn eval(&self, wi: &Vec3, _wo: &Vec3, rec: &HitRecord) -> Color {
+        let cosine = Vec3::dot(rec.normal(), wi).max(0.0);
+        (cosine / PI) * self.albedo.value(rec.uv(), rec.p())
+    }
+
+    fn pdf(&self, wi: &Vec3, _wo: &Vec3, rec: &HitRecord) -> f64 {
+        // `scatter` is cosine weighted
+        Vec3::dot(rec.normal(), wi).max(0.0) / PI
     }
 }
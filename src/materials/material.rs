@@ -1,33 +1,172 @@
-use crate::{ray::Ray, shapes::HitRecord, Color};
+use crate::{render::sampler::Sampler, ray::Ray, shapes::HitRecord, Color, Texture, Vec3};
 
-use super::{dielectric::Dielectric, lambertian::Lambertian, metal::Metal};
+use super::{
+    dielectric::Dielectric, diffuse_light::DiffuseLight, henyey_greenstein::HenyeyGreenstein,
+    isotropic::Isotropic, lambertian::Lambertian, metal::Metal,
+};
+
+/// Incoming light direction picked by [`Scatter::sample`].
+pub struct BsdfSample {
+    wi: Vec3,
+    weight: Color,
+    pdf: Option<f64>,
+}
+
+impl BsdfSample {
+    pub fn new(wi: Vec3, weight: Color, pdf: Option<f64>) -> Self {
+        Self {
+            wi: Vec3::unit_vector(&wi),
+            weight,
+            pdf,
+        }
+    }
+
+    /// Get the sample's unit direction, pointing away from the surface.
+    pub fn wi(&self) -> &Vec3 {
+        &self.wi
+    }
+
+    /// Get the sample's weight: `eval(wi, wo) / pdf(wi, wo)`, or the attenuation for
+    /// specular samples.
+    pub fn weight(&self) -> Color {
+        self.weight
+    }
+
+    /// Get the sample's solid angle density, `None` for specular directions.
+    pub fn pdf(&self) -> Option<f64> {
+        self.pdf
+    }
+}
 
 pub trait Scatter: Sync + Send {
-    fn scatter(&self, r: &Ray, rec: &HitRecord) -> Option<(Ray, Color)>;
+    /// Picks the direction light arrives from when leaving the surface towards `wo`,
+    /// or `None` if the light gets absorbed.
+    fn sample(&self, wo: &Vec3, rec: &HitRecord, sampler: &mut dyn Sampler) -> Option<BsdfSample>;
+
+    fn scatter(&self, r: &Ray, rec: &HitRecord, sampler: &mut dyn Sampler) -> Option<(Ray, Color)> {
+        self.sample(&-Vec3::unit_vector(r.dir()), rec, sampler)
+            .map(|sample| {
+                let scattered = Ray::new(*rec.p(), *sample.wi()).with_time(r.time());
+                (scattered, sample.weight())
+            })
+    }
+
+    /// Light emitted by the surface towards the incoming ray. Black for non-emissive materials.
+    fn emitted(&self, _r: &Ray, _rec: &HitRecord) -> Color {
+        Color::default()
+    }
+
+    /// Scattering function for light arriving from `wi` and leaving towards `wo`, both unit
+    /// vectors pointing away from the surface, including the cosine of `wi` with the normal.
+    /// Black for perfectly specular materials, which can only be evaluated by `sample`.
+    fn eval(&self, _wi: &Vec3, _wo: &Vec3, _rec: &HitRecord) -> Color {
+        Color::default()
+    }
+
+    /// Solid angle density with which `sample` picks the direction `wi` when looking from `wo`.
+    /// Zero for perfectly specular materials.
+    fn pdf(&self, _wi: &Vec3, _wo: &Vec3, _rec: &HitRecord) -> f64 {
+        0.0
+    }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub enum Material {
     Dielectric { ir: f64 },
-    Lambertian { albedo: Color },
-    Metal { albedo: Color, fuzz: f64 },
+    Lambertian { albedo: Texture },
+    /// Mirror blurred by `fuzz`, the radius of the ball around the mirror direction that
+    /// reflections are picked from. Only `fuzz == 0` is perfectly specular.
+    Metal { albedo: Texture, fuzz: f64 },
+    DiffuseLight { emit: Color },
+    /// Scatters uniformly in all directions. Meant for participating media such as
+    /// [`crate::Shape::ConstantMedium`].
+    Isotropic { albedo: Texture },
+    /// Medium scattering mostly forward (`g > 0`) or backward (`g < 0`), with `g` in
+    /// `(-1, 1)`.
+    HenyeyGreenstein { albedo: Texture, g: f64 },
 }
 
 impl Scatter for Material {
-    fn scatter(&self, r: &Ray, rec: &HitRecord) -> Option<(Ray, Color)> {
+    fn sample(&self, wo: &Vec3, rec: &HitRecord, sampler: &mut dyn Sampler) -> Option<BsdfSample> {
         match self {
             Material::Dielectric { ir } => {
                 let material = Dielectric::new(*ir);
-                material.scatter(r, rec)
+                material.sample(wo, rec, sampler)
+            }
+            Material::Lambertian { albedo } => {
+                let material = Lambertian::new(albedo);
+                material.sample(wo, rec, sampler)
+            }
+            Material::Metal { albedo, fuzz } => {
+                let material = Metal::new(albedo, *fuzz);
+                material.sample(wo, rec, sampler)
             }
+            Material::DiffuseLight { emit } => {
+                let material = DiffuseLight::new(emit);
+                material.sample(wo, rec, sampler)
+            }
+            Material::Isotropic { albedo } => {
+                let material = Isotropic::new(albedo);
+                material.sample(wo, rec, sampler)
+            }
+            Material::HenyeyGreenstein { albedo, g } => {
+                let material = HenyeyGreenstein::new(albedo, *g);
+                material.sample(wo, rec, sampler)
+            }
+        }
+    }
+
+    fn emitted(&self, r: &Ray, rec: &HitRecord) -> Color {
+        match self {
+            Material::DiffuseLight { emit } => {
+                let material = DiffuseLight::new(emit);
+                material.emitted(r, rec)
+            }
+            _ => Color::default(),
+        }
+    }
+
+    fn eval(&self, wi: &Vec3, wo: &Vec3, rec: &HitRecord) -> Color {
+        match self {
+            Material::Lambertian { albedo } => {
+                let material = Lambertian::new(albedo);
+                material.eval(wi, wo, rec)
+            }
+            Material::Metal { albedo, fuzz } => {
+                let material = Metal::new(albedo, *fuzz);
+                material.eval(wi, wo, rec)
+            }
+            Material::Isotropic { albedo } => {
+                let material = Isotropic::new(albedo);
+                material.eval(wi, wo, rec)
+            }
+            Material::HenyeyGreenstein { albedo, g } => {
+                let material = HenyeyGreenstein::new(albedo, *g);
+                material.eval(wi, wo, rec)
+            }
+            _ => Color::default(),
+        }
+    }
+
+    fn pdf(&self, wi: &Vec3, wo: &Vec3, rec: &HitRecord) -> f64 {
+        match self {
             Material::Lambertian { albedo } => {
                 let material = Lambertian::new(albedo);
-                material.scatter(r, rec)
+                material.pdf(wi, wo, rec)
             }
             Material::Metal { albedo, fuzz } => {
                 let material = Metal::new(albedo, *fuzz);
-                material.scatter(r, rec)
+                material.pdf(wi, wo, rec)
+            }
+            Material::Isotropic { albedo } => {
+                let material = Isotropic::new(albedo);
+                material.pdf(wi, wo, rec)
+            }
+            Material::HenyeyGreenstein { albedo, g } => {
+                let material = HenyeyGreenstein::new(albedo, *g);
+                material.pdf(wi, wo, rec)
             }
+            _ => 0.0,
         }
     }
 }
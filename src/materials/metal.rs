@@ -1,36 +1,107 @@
-use crate::{ray::Ray, Color, Vec3, shapes::HitRecord};
+use std::f64::consts::PI;
 
-use super::material::Scatter;
+use crate::{render::sampler::Sampler, shapes::HitRecord, Color, Texture, TextureValue, Vec3};
 
-pub struct Metal {
-    albedo: Color,
+use super::material::{BsdfSample, Scatter};
+
+pub struct Metal<'a> {
+    albedo: &'a Texture,
     fuzz: f64,
 }
 
-impl Metal {
-    pub fn new(albedo: &Color, fuzz: f64) -> Self {
-        Self {
-            albedo: *albedo,
-            fuzz,
+impl<'a> Metal<'a> {
+    pub fn new(albedo: &'a Texture, fuzz: f64) -> Self {
+        Self { albedo, fuzz }
+    }
+}
+
+impl Scatter for Metal<'_> {
+    fn sample(&self, wo: &Vec3, rec: &HitRecord, sampler: &mut dyn Sampler) -> Option<BsdfSample> {
+        let reflected = Vec3::reflect(&-Vec3::unit_vector(wo), rec.normal());
+        let albedo = self.albedo.value(rec.uv(), rec.p());
+        if self.fuzz == 0.0 {
+            return Some(BsdfSample::new(reflected, albedo, None));
+        }
+
+        let (u1, u2) = sampler.get_2d();
+        let fuzz = Vec3::sample_in_unit_sphere(u1, u2, sampler.get_1d());
+        let direction = reflected + self.fuzz * fuzz;
+        if direction.near_zero() || Vec3::dot(&direction, rec.normal()) <= 0.0 {
+            return None;
         }
+
+        // Directions below the surface are absorbed, so only the albedo is left
+        let wi = Vec3::unit_vector(&direction);
+        let pdf = self.pdf(&wi, wo, rec);
+        Some(BsdfSample::new(wi, albedo, Some(pdf)))
+    }
+
+    fn eval(&self, wi: &Vec3, wo: &Vec3, rec: &HitRecord) -> Color {
+        self.pdf(wi, wo, rec) * self.albedo.value(rec.uv(), rec.p())
+    }
+
+    fn pdf(&self, wi: &Vec3, wo: &Vec3, rec: &HitRecord) -> f64 {
+        let radius = self.fuzz.abs();
+        if radius == 0.0 || Vec3::dot(wi, rec.normal()) <= 0.0 {
+            return 0.0;
+        }
+
+        // `sample` picks a point uniformly in the ball of radius `fuzz` around the mirror
+        // direction. The density of `wi` is the part of the ball's volume along it, found by
+        // integrating s² ds over the segment where the ray s * wi is inside the ball.
+        let reflected = Vec3::reflect(&-Vec3::unit_vector(wo), rec.normal());
+        let b = Vec3::dot(wi, &reflected);
+        let discriminant = b * b - 1.0 + radius * radius;
+        if discriminant <= 0.0 {
+            return 0.0;
+        }
+        let s_far = b + discriminant.sqrt();
+        let s_near = (b - discriminant.sqrt()).max(0.0);
+        if s_far <= 0.0 {
+            return 0.0;
+        }
+        (s_far.powi(3) - s_near.powi(3)) / (4.0 * PI * radius.powi(3))
     }
 }
 
-impl Scatter for Metal {
-    fn scatter(
-        &self,
-        r: &Ray,
-        rec: &HitRecord,
-    ) -> Option<(crate::ray::Ray, Color)> {
-        let reflected = Vec3::reflect(&Vec3::unit_vector(r.dir()), rec.normal());
-        let scattered = Ray::new(
-            *rec.p(),
-            reflected + self.fuzz * Vec3::random_in_unit_sphere(),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ray::Ray,
+        render::sampler::{IndependentSampler, Sampler},
+        Point3,
+    };
+
+    #[test]
+    fn fuzzy_pdf_integrates_to_one() {
+        let albedo = Texture::from(Color::new(0.8, 0.6, 0.4));
+        let metal = Metal::new(&albedo, 0.5);
+        let r = Ray::new(Point3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0));
+        let rec = HitRecord::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            1.0,
+            &r,
         );
-        if Vec3::dot(scattered.dir(), rec.normal()) > 0.0 {
-            Some((scattered, self.albedo))
-        } else {
-            None
-        }
+        let wo = Vec3::new(0.0, 0.0, 1.0);
+
+        // Uniform directions over the sphere, so every sample is weighted by 4π
+        let mut sampler = IndependentSampler::new(1);
+        let samples = 200000;
+        let total: f64 = (0..samples)
+            .map(|_| {
+                let (u1, u2) = sampler.get_2d();
+                metal.pdf(&Vec3::sample_unit_sphere(u1, u2), &wo, &rec)
+            })
+            .sum();
+        let integral = 4.0 * PI * total / samples as f64;
+        assert!((integral - 1.0).abs() < 0.05, "{}", integral);
+
+        let sample = metal.sample(&wo, &rec, &mut sampler).unwrap();
+        let pdf = sample.pdf().unwrap();
+        assert!((pdf - metal.pdf(sample.wi(), &wo, &rec)).abs() < 1e-9);
+        let f = metal.eval(sample.wi(), &wo, &rec);
+        assert!((f.x() / pdf - 0.8).abs() < 1e-9);
     }
 }
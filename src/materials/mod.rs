@@ -1,7 +1,10 @@
 mod dielectric;
+mod diffuse_light;
+mod henyey_greenstein;
+mod isotropic;
 mod lambertian;
 mod material;
 mod metal;
 
 pub type Material = material::Material;
-pub use material::Scatter;
\ No newline at end of file
+pub use material::{BsdfSample, Scatter};
\ No newline at end of file
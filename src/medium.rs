@@ -0,0 +1,171 @@
+use crate::{ray::Ray, render::sampler::Sampler, Point3, Vec3, VoxelGrid};
+
+/// Density of a participating medium, recorded in the hits of volumetric shapes.
+///
+/// The values of a `Grid` medium live in the [`VoxelGrid`] of the shape that was hit, which
+/// the methods below take as `grid`. Without it a `Grid` medium is empty.
+#[derive(Clone, Copy)]
+pub enum Medium {
+    Homogeneous {
+        density: f64,
+    },
+    /// Grid values scaled by `density`. `origin` and `direction` are the ray that entered the
+    /// medium mapped into the grid's unit cube, so `t` means the same along both rays.
+    Grid {
+        density: f64,
+        origin: Point3,
+        direction: Vec3,
+    },
+}
+
+impl Medium {
+    /// Density where the ray that entered the medium is at `t`.
+    pub fn density(&self, grid: Option<&VoxelGrid>, t: f64) -> f64 {
+        match self {
+            Medium::Homogeneous { density } => *density,
+            Medium::Grid {
+                density,
+                origin,
+                direction,
+            } => grid.map_or(0.0, |grid| {
+                density * grid.value(&(*origin + *direction * t))
+            }),
+        }
+    }
+
+    /// Upper bound of the density anywhere in the medium.
+    pub fn majorant(&self, grid: Option<&VoxelGrid>) -> f64 {
+        match self {
+            Medium::Homogeneous { density } => *density,
+            Medium::Grid { density, .. } => grid.map_or(0.0, |grid| density * grid.max_value()),
+        }
+    }
+
+    /// Samples where the ray first collides with the medium between `t_enter` and `t_exit`,
+    /// or `None` if it gets through. Non-uniform media use delta tracking.
+    pub(crate) fn sample_collision(
+        &self,
+        grid: Option<&VoxelGrid>,
+        ray: &Ray,
+        t_enter: f64,
+        t_exit: f64,
+        sampler: &mut dyn Sampler,
+    ) -> Option<f64> {
+        let majorant = self.majorant(grid);
+        if majorant <= 0.0 {
+            return None;
+        }
+
+        let mut t = t_enter;
+        loop {
+            // Exponentially distributed distance, converted to the ray's parameterization
+            t -= (1.0 - sampler.get_1d()).ln() / (majorant * ray.dir().len());
+            if t >= t_exit {
+                return None;
+            }
+            // Tentative collisions are real with probability density / majorant
+            let real = match self {
+                Medium::Homogeneous { .. } => true,
+                Medium::Grid { .. } => sampler.get_1d() * majorant < self.density(grid, t),
+            };
+            if real {
+                return Some(t);
+            }
+        }
+    }
+
+    /// Fraction of light that passes through the medium between `t_enter` and `t_exit`.
+    /// Non-uniform media estimate it with ratio tracking.
+    pub(crate) fn transmittance(
+        &self,
+        grid: Option<&VoxelGrid>,
+        ray: &Ray,
+        t_enter: f64,
+        t_exit: f64,
+        sampler: &mut dyn Sampler,
+    ) -> f64 {
+        if let Medium::Homogeneous { density } = self {
+            return (-density * (t_exit - t_enter) * ray.dir().len()).exp();
+        }
+        let majorant = self.majorant(grid);
+        if majorant <= 0.0 {
+            return 1.0;
+        }
+
+        let mut transmittance = 1.0;
+        let mut t = t_enter;
+        loop {
+            t -= (1.0 - sampler.get_1d()).ln() / (majorant * ray.dir().len());
+            if t >= t_exit {
+                return transmittance;
+            }
+            transmittance *= 1.0 - self.density(grid, t) / majorant;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::sampler::IndependentSampler;
+
+    const SAMPLES: usize = 20000;
+
+    // Ray covering a distance of 3 between `t = 0` and `t = 1.5`, through a constant grid
+    // with the same density as the homogeneous medium
+    fn media() -> (Ray, VoxelGrid, Medium, Medium) {
+        let ray = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 2.0));
+        let grid = VoxelGrid::new([1, 1, 1], vec![1.0]).unwrap();
+        let homogeneous = Medium::Homogeneous { density: 0.5 };
+        let constant_grid = Medium::Grid {
+            density: 0.5,
+            origin: Point3::new(0.5, 0.5, 0.1),
+            direction: Vec3::new(0.0, 0.0, 0.4),
+        };
+        (ray, grid, homogeneous, constant_grid)
+    }
+
+    #[test]
+    fn constant_grid_transmittance_matches_homogeneous() {
+        let (ray, grid, homogeneous, constant_grid) = media();
+        let mut sampler = IndependentSampler::new(1);
+        let expected = (-0.5f64 * 3.0).exp();
+        let exact = homogeneous.transmittance(None, &ray, 0.0, 1.5, &mut sampler);
+        assert!((exact - expected).abs() < 1e-12);
+
+        let total: f64 = (0..SAMPLES)
+            .map(|_| constant_grid.transmittance(Some(&grid), &ray, 0.0, 1.5, &mut sampler))
+            .sum();
+        let average = total / SAMPLES as f64;
+        assert!((average - expected).abs() < 0.015, "{}", average);
+    }
+
+    #[test]
+    fn constant_grid_escape_rate_matches_homogeneous() {
+        let (ray, grid, homogeneous, constant_grid) = media();
+        let mut sampler = IndependentSampler::new(2);
+        let expected = (-0.5f64 * 3.0).exp();
+        for (medium, grid) in [(homogeneous, None), (constant_grid, Some(&grid))] {
+            let escaped = (0..SAMPLES)
+                .filter(|_| {
+                    medium
+                        .sample_collision(grid, &ray, 0.0, 1.5, &mut sampler)
+                        .is_none()
+                })
+                .count();
+            let rate = escaped as f64 / SAMPLES as f64;
+            assert!((rate - expected).abs() < 0.015, "{}", rate);
+        }
+    }
+
+    #[test]
+    fn grid_without_voxels_is_empty() {
+        let (ray, _, _, constant_grid) = media();
+        let mut sampler = IndependentSampler::new(3);
+        assert_eq!(constant_grid.majorant(None), 0.0);
+        assert_eq!(
+            constant_grid.transmittance(None, &ray, 0.0, 1.5, &mut sampler),
+            1.0
+        );
+    }
+}
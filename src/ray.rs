@@ -1,13 +1,25 @@
 use crate::{Point3, Vec3};
 
+#[derive(Clone, Copy)]
 pub struct Ray {
     orig: Point3,
     dir: Vec3,
+    time: f64,
 }
 
 impl Ray {
     pub fn new(orig: Point3, dir: Vec3) -> Self {
-        Self { orig, dir }
+        Self {
+            orig,
+            dir,
+            time: 0.0,
+        }
+    }
+
+    /// Set the moment the ray exists at, for shapes that move while the shutter is open.
+    pub fn with_time(mut self, time: f64) -> Self {
+        self.time = time;
+        self
     }
 
     pub fn at(&self, t: f64) -> Point3 {
@@ -23,4 +35,9 @@ impl Ray {
     pub fn dir(&self) -> &Vec3 {
         &self.dir
     }
+
+    /// Get the ray's time.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
 }
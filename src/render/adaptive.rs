@@ -0,0 +1,125 @@
+use std::time::Duration;
+
+use crate::Color;
+
+/// Settings for rendering in passes that only keep sampling pixels which have not converged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveSampling {
+    threshold: f64,
+    pass_samples: u32,
+    max_samples: u32,
+    time_budget: Option<Duration>,
+}
+
+impl Default for AdaptiveSampling {
+    fn default() -> Self {
+        Self {
+            threshold: 0.01,
+            pass_samples: 16,
+            max_samples: 1024,
+            time_budget: None,
+        }
+    }
+}
+
+impl AdaptiveSampling {
+    /// A pixel stops receiving samples once the standard error of its luminance, relative to
+    /// the luminance itself, falls below `threshold`.
+    pub fn new(threshold: f64) -> Self {
+        Self {
+            threshold,
+            ..Self::default()
+        }
+    }
+
+    /// Set the number of samples every unconverged pixel gets per pass.
+    pub fn with_pass_samples(mut self, pass_samples: u32) -> Self {
+        self.pass_samples = pass_samples.max(2);
+        self
+    }
+
+    /// Set the number of samples after which a pixel counts as done, converged or not.
+    pub fn with_max_samples(mut self, max_samples: u32) -> Self {
+        self.max_samples = max_samples.max(1);
+        self
+    }
+
+    /// Stop starting new passes once rendering took longer than `time_budget`.
+    pub fn with_time_budget(mut self, time_budget: Duration) -> Self {
+        self.time_budget = Some(time_budget);
+        self
+    }
+
+    /// Get the adaptive sampling's threshold.
+    pub fn threshold(&self) -> f64 {
+        self.threshold
+    }
+
+    /// Get the adaptive sampling's samples per pass.
+    pub fn pass_samples(&self) -> u32 {
+        self.pass_samples
+    }
+
+    /// Get the adaptive sampling's maximum samples per pixel.
+    pub fn max_samples(&self) -> u32 {
+        self.max_samples
+    }
+
+    /// Get the adaptive sampling's time budget.
+    pub fn time_budget(&self) -> Option<Duration> {
+        self.time_budget
+    }
+
+    /// Number of passes needed to reach the sample cap.
+    pub(crate) fn passes(&self) -> u32 {
+        self.max_samples.div_ceil(self.pass_samples)
+    }
+
+    /// Samples taken in `pass`, as a range of sample indices.
+    pub(crate) fn pass_range(&self, pass: u32) -> std::ops::Range<u32> {
+        let start = pass * self.pass_samples;
+        start..(start + self.pass_samples).min(self.max_samples)
+    }
+}
+
+/// Running mean and luminance variance of the samples taken for one pixel.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct PixelEstimate {
+    sum: Color,
+    luminance_sum: f64,
+    luminance_squared_sum: f64,
+    samples: u32,
+}
+
+impl PixelEstimate {
+    pub(crate) fn add(&mut self, sample: Color) {
+        let luminance = sample.luminance();
+        self.sum += sample;
+        self.luminance_sum += luminance;
+        self.luminance_squared_sum += luminance * luminance;
+        self.samples += 1;
+    }
+
+    pub(crate) fn mean(&self) -> Color {
+        if self.samples == 0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+        self.sum / self.samples as f64
+    }
+
+    /// Standard error of the mean luminance relative to the mean itself. Dark pixels get
+    /// judged against a small floor instead, so that noise in them doesn't keep them open forever.
+    pub(crate) fn relative_error(&self) -> f64 {
+        if self.samples < 2 {
+            return f64::INFINITY;
+        }
+        let n = self.samples as f64;
+        let mean = self.luminance_sum / n;
+        let variance = ((self.luminance_squared_sum - n * mean * mean) / (n - 1.0)).max(0.0);
+        (variance / n).sqrt() / mean.max(0.01)
+    }
+
+    pub(crate) fn is_done(&self, settings: &AdaptiveSampling) -> bool {
+        self.samples >= settings.max_samples || self.relative_error() < settings.threshold
+    }
+}
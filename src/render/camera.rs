@@ -2,6 +2,8 @@ use std::f64::consts::PI;
 
 use crate::{ray::Ray, Point3, Vec3};
 
+use super::sampler::Sampler;
+
 pub struct Camera {
     origin: Point3,
     lower_left_corner: Point3,
@@ -11,6 +13,9 @@ pub struct Camera {
     u: Vec3,
     v: Vec3,
     lens_radius: f64,
+    // Shutter open and close times
+    time0: f64,
+    time1: f64,
 }
 
 impl Camera {
@@ -48,17 +53,39 @@ impl Camera {
             _w: w,
             u,
             v,
+            time0: 0.0,
+            time1: 0.0,
         }
     }
 
-    pub fn get_ray(&self, s: f64, t: f64) -> Ray {
-        let rd = self.lens_radius * Vec3::random_in_unit_disk();
+    /// Keep the shutter open from `open` to `close`; every ray gets a random time in between.
+    pub fn with_shutter(mut self, open: f64, close: f64) -> Self {
+        self.time0 = open;
+        self.time1 = close;
+        self
+    }
+
+    /// Get the camera's shutter open time.
+    pub fn shutter_open(&self) -> f64 {
+        self.time0
+    }
+
+    /// Get the camera's shutter close time.
+    pub fn shutter_close(&self) -> f64 {
+        self.time1
+    }
+
+    pub fn get_ray(&self, s: f64, t: f64, sampler: &mut dyn Sampler) -> Ray {
+        let (u1, u2) = sampler.get_2d();
+        let rd = self.lens_radius * Vec3::sample_in_unit_disk(u1, u2);
         let offset = self.u * rd.x() + self.v * rd.y();
 
         let direction =
             self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset;
 
-        Ray::new(self.origin + offset, direction)
+        let time = self.time0 + sampler.get_1d() * (self.time1 - self.time0);
+
+        Ray::new(self.origin + offset, direction).with_time(time)
     }
 
     fn degrees_to_radians(degrees: f64) -> f64 {
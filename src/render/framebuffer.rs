@@ -0,0 +1,132 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use crate::Color;
+
+use super::{output::OutputFormat, tile::Tile, tonemap::ToneMapping};
+
+/// Rendered image holding linear radiance per pixel, stored row by row from the top.
+#[derive(Debug, Clone)]
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Framebuffer {
+    /// Creates a black image.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self::from_pixels(width, height, vec![Color::default(); width * height])
+    }
+
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert_eq!(width * height, pixels.len());
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Get the image's width.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the image's height.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Get the color of the pixel in column `x` and row `y`, counting rows from the top.
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+        let index = self.index(x, y);
+        self.pixels[index] = color;
+    }
+
+    /// Copies the pixels of `tile`, given row by row, into the image.
+    pub fn set_tile(&mut self, tile: &Tile, pixels: &[Color]) {
+        assert_eq!(tile.len(), pixels.len());
+        for (row, colors) in pixels.chunks_exact(tile.width().max(1)).enumerate() {
+            let start = self.index(tile.x(), tile.y() + row);
+            self.pixels[start..start + tile.width()].copy_from_slice(colors);
+        }
+    }
+
+    /// Get a reference to all pixels.
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    /// Get a mutable reference to all pixels.
+    pub fn pixels_mut(&mut self) -> &mut [Color] {
+        &mut self.pixels
+    }
+
+    /// Iterates over `(x, y, color)` of every pixel, row by row from the top.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &Color)> + '_ {
+        self.pixels
+            .iter()
+            .enumerate()
+            .map(move |(i, color)| (i % self.width, i / self.width, color))
+    }
+
+    /// Iterates over the image rows from the top.
+    pub fn rows(&self) -> impl Iterator<Item = &[Color]> + '_ {
+        self.pixels.chunks_exact(self.width.max(1))
+    }
+
+    fn index(&self, x: usize, y: usize) -> usize {
+        assert!(x < self.width && y < self.height);
+        y * self.width + x
+    }
+
+    /// Display-ready 8-bit RGB values.
+    pub fn to_rgb8(&self, tone_mapping: &ToneMapping) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|color| tone_mapping.encode(color))
+            .collect()
+    }
+
+    /// Writes the image to a file, picking the format from the path's extension
+    /// and using the default tone mapping.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let format = OutputFormat::from_path(&path);
+        self.save_as(path, format, &ToneMapping::default())
+    }
+
+    pub fn save_as<P: AsRef<Path>>(
+        &self,
+        path: P,
+        format: OutputFormat,
+        tone_mapping: &ToneMapping,
+    ) -> io::Result<()> {
+        let file = File::create(path)?;
+        self.write(BufWriter::new(file), format, tone_mapping)
+    }
+
+    /// Encodes the image into `writer`. High dynamic range formats get the linear
+    /// values, so `tone_mapping` only applies to 8-bit formats.
+    pub fn write<W: Write>(
+        &self,
+        writer: W,
+        format: OutputFormat,
+        tone_mapping: &ToneMapping,
+    ) -> io::Result<()> {
+        let width = self.width as u32;
+        let height = self.height as u32;
+        if format.is_high_dynamic_range() {
+            format.encode_hdr(writer, width, height, &self.pixels)
+        } else {
+            format.encode(writer, width, height, &self.to_rgb8(tone_mapping))
+        }
+    }
+}
@@ -1,2 +1,9 @@
 pub mod renderer;
-pub mod camera;
\ No newline at end of file
+pub mod adaptive;
+pub mod camera;
+pub mod framebuffer;
+pub mod observer;
+pub mod output;
+pub mod sampler;
+pub mod tile;
+pub mod tonemap;
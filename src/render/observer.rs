@@ -0,0 +1,128 @@
+use std::{
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::Color;
+
+use super::{framebuffer::Framebuffer, tile::Tile};
+
+/// Receives progress notifications while a [`super::renderer::Renderer`] works.
+///
+/// Tile and progress notifications come from the render threads, in no particular order, so
+/// implementations should return quickly.
+pub trait RenderObserver: Send + Sync {
+    /// Called once before any pixel is rendered.
+    fn render_started(&self, _width: usize, _height: usize) {}
+
+    /// Called as soon as a tile is done, with its pixels row by row.
+    fn tile_finished(&self, _tile: &Tile, _pixels: &[Color]) {}
+
+    /// Called after every pass with the image rendered so far. Renders without adaptive
+    /// sampling consist of a single pass.
+    fn pass_finished(&self, _pass: u32, _image: &Framebuffer) {}
+
+    /// Called after every tile with the fraction of the work done, from 0 to 1, and the time
+    /// since rendering started.
+    fn progress(&self, _fraction: f64, _elapsed: Duration) {}
+
+    /// Called once when rendering stops, either finished or cancelled.
+    fn render_finished(&self, _elapsed: Duration, _cancelled: bool) {}
+}
+
+/// Renders quietly.
+impl RenderObserver for () {}
+
+impl<T: RenderObserver + ?Sized> RenderObserver for Arc<T> {
+    fn render_started(&self, width: usize, height: usize) {
+        (**self).render_started(width, height)
+    }
+
+    fn tile_finished(&self, tile: &Tile, pixels: &[Color]) {
+        (**self).tile_finished(tile, pixels)
+    }
+
+    fn pass_finished(&self, pass: u32, image: &Framebuffer) {
+        (**self).pass_finished(pass, image)
+    }
+
+    fn progress(&self, fraction: f64, elapsed: Duration) {
+        (**self).progress(fraction, elapsed)
+    }
+
+    fn render_finished(&self, elapsed: Duration, cancelled: bool) {
+        (**self).render_finished(elapsed, cancelled)
+    }
+}
+
+const PROGRESS_STEPS: u64 = 1000;
+
+/// Shows a progress bar on the terminal and prints the render time at the end.
+#[derive(Default)]
+pub struct ProgressBarObserver {
+    bar: Mutex<Option<ProgressBar>>,
+}
+
+impl ProgressBarObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RenderObserver for ProgressBarObserver {
+    fn render_started(&self, _width: usize, _height: usize) {
+        let pb = ProgressBar::new(PROGRESS_STEPS);
+        pb.set_style(ProgressStyle::default_bar().template(
+            "{spinner:.green} {msg} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {percent}% ({eta_precise})",
+        ));
+        pb.set_message("Generating image pixels");
+        *self.bar.lock().unwrap() = Some(pb);
+    }
+
+    fn progress(&self, fraction: f64, _elapsed: Duration) {
+        if let Some(pb) = self.bar.lock().unwrap().as_ref() {
+            pb.set_position((fraction * PROGRESS_STEPS as f64) as u64);
+        }
+    }
+
+    fn render_finished(&self, elapsed: Duration, cancelled: bool) {
+        if let Some(pb) = self.bar.lock().unwrap().take() {
+            pb.finish_at_current_pos();
+        }
+        if cancelled {
+            println!("Rendering cancelled after: {:.2?}", elapsed);
+        } else {
+            println!("Rendering took: {:.2?}", elapsed);
+        }
+        io::stdout().flush().unwrap();
+    }
+}
+
+/// Shared flag for stopping a render from another thread.
+///
+/// Clones refer to the same flag. A cancelled render returns early with the tiles that were
+/// finished by then; the rest of the image stays black.
+#[derive(Debug, Default, Clone)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
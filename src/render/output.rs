@@ -0,0 +1,214 @@
+use std::{
+    io::{self, Cursor, ErrorKind, Write},
+    path::Path,
+};
+
+use crate::Color;
+
+/// Image file format written by the renderer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// ASCII PPM (`P3`)
+    PpmAscii,
+    /// Binary PPM (`P6`)
+    PpmBinary,
+    Png,
+    /// Portable float map, linear 32-bit floats
+    Pfm,
+    /// Radiance RGBE (`.hdr`), linear
+    Hdr,
+    /// OpenEXR, linear 32-bit floats
+    Exr,
+}
+
+impl OutputFormat {
+    /// Picks the format from the file extension. Paths with an unknown or missing
+    /// extension fall back to ASCII PPM.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("png") => OutputFormat::Png,
+            Some("pfm") => OutputFormat::Pfm,
+            Some("hdr") => OutputFormat::Hdr,
+            Some("exr") => OutputFormat::Exr,
+            _ => OutputFormat::PpmAscii,
+        }
+    }
+
+    /// Whether the format stores linear floating point radiance instead of 8-bit display values.
+    pub fn is_high_dynamic_range(&self) -> bool {
+        matches!(
+            self,
+            OutputFormat::Pfm | OutputFormat::Hdr | OutputFormat::Exr
+        )
+    }
+
+    /// Encodes 8-bit RGB pixels stored row by row from the top of the image.
+    pub fn encode<W: Write>(
+        &self,
+        writer: W,
+        width: u32,
+        height: u32,
+        rgb: &[u8],
+    ) -> io::Result<()> {
+        match self {
+            OutputFormat::PpmAscii => Self::encode_ppm_ascii(writer, width, height, rgb),
+            OutputFormat::PpmBinary => Self::encode_ppm_binary(writer, width, height, rgb),
+            OutputFormat::Png => Self::encode_png(writer, width, height, rgb),
+            _ => Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("{:?} stores linear colors, not 8-bit pixels", self),
+            )),
+        }
+    }
+
+    /// Encodes linear colors stored row by row from the top of the image.
+    pub fn encode_hdr<W: Write>(
+        &self,
+        writer: W,
+        width: u32,
+        height: u32,
+        pixels: &[Color],
+    ) -> io::Result<()> {
+        match self {
+            OutputFormat::Pfm => Self::encode_pfm(writer, width, height, pixels),
+            OutputFormat::Hdr => Self::encode_radiance(writer, width, height, pixels),
+            OutputFormat::Exr => Self::encode_exr(writer, width, height, pixels),
+            _ => Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                format!("{:?} stores 8-bit pixels, not linear colors", self),
+            )),
+        }
+    }
+
+    fn encode_ppm_ascii<W: Write>(
+        mut writer: W,
+        width: u32,
+        height: u32,
+        rgb: &[u8],
+    ) -> io::Result<()> {
+        // Image header
+        writeln!(writer, "P3")?;
+        writeln!(writer, "{} {}", width, height)?;
+        writeln!(writer, "255")?;
+
+        // Image data
+        for pixel in rgb.chunks_exact(3) {
+            writeln!(writer, "{} {} {}", pixel[0], pixel[1], pixel[2])?;
+        }
+        writer.flush()
+    }
+
+    fn encode_ppm_binary<W: Write>(
+        mut writer: W,
+        width: u32,
+        height: u32,
+        rgb: &[u8],
+    ) -> io::Result<()> {
+        write!(writer, "P6\n{} {}\n255\n", width, height)?;
+        writer.write_all(rgb)?;
+        writer.flush()
+    }
+
+    fn encode_png<W: Write>(writer: W, width: u32, height: u32, rgb: &[u8]) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(writer, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer.write_image_data(rgb).map_err(io::Error::other)?;
+        writer.finish().map_err(io::Error::other)
+    }
+
+    fn encode_pfm<W: Write>(
+        mut writer: W,
+        width: u32,
+        height: u32,
+        pixels: &[Color],
+    ) -> io::Result<()> {
+        // Negative scale means little endian
+        write!(writer, "PF\n{} {}\n-1.0\n", width, height)?;
+
+        // Rows are stored from the bottom of the image
+        for row in pixels.chunks_exact(width as usize).rev() {
+            for pixel in row {
+                for c in [pixel.x(), pixel.y(), pixel.z()] {
+                    writer.write_all(&(c as f32).to_le_bytes())?;
+                }
+            }
+        }
+        writer.flush()
+    }
+
+    fn encode_radiance<W: Write>(
+        mut writer: W,
+        width: u32,
+        height: u32,
+        pixels: &[Color],
+    ) -> io::Result<()> {
+        write!(
+            writer,
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            height, width
+        )?;
+
+        // Flat (not run-length encoded) scanlines
+        for pixel in pixels {
+            writer.write_all(&Self::color_to_rgbe(pixel))?;
+        }
+        writer.flush()
+    }
+
+    fn color_to_rgbe(color: &Color) -> [u8; 4] {
+        let r = color.x().max(0.0);
+        let g = color.y().max(0.0);
+        let b = color.z().max(0.0);
+        let v = r.max(g).max(b);
+        if v < 1e-32 {
+            return [0; 4];
+        }
+
+        // v = m * 2^e with m in [0.5, 1)
+        let mut e = v.log2().floor() as i32 + 1;
+        if v / 2f64.powi(e) >= 1.0 {
+            e += 1;
+        }
+        let scale = 256.0 / 2f64.powi(e);
+
+        [
+            (r * scale) as u8,
+            (g * scale) as u8,
+            (b * scale) as u8,
+            (e + 128).clamp(0, 255) as u8,
+        ]
+    }
+
+    fn encode_exr<W: Write>(
+        mut writer: W,
+        width: u32,
+        height: u32,
+        pixels: &[Color],
+    ) -> io::Result<()> {
+        use exr::prelude::{Image, SpecificChannels, Vec2, WritableImage};
+
+        let channels = SpecificChannels::rgb(|position: Vec2<usize>| {
+            let pixel = pixels[position.y() * width as usize + position.x()];
+            (pixel.x() as f32, pixel.y() as f32, pixel.z() as f32)
+        });
+        let image = Image::from_channels((width as usize, height as usize), channels);
+
+        // The encoder needs to seek, so build the file in memory first
+        let mut buffer = Cursor::new(Vec::new());
+        image
+            .write()
+            .to_buffered(&mut buffer)
+            .map_err(io::Error::other)?;
+        writer.write_all(buffer.get_ref())?;
+        writer.flush()
+    }
+}
@@ -1,166 +1,499 @@
+use crate::light::Light;
 use crate::materials::Scatter;
 use crate::scene::Scene;
-use crate::{ray::Ray, Color, Vec3};
-use indicatif::{ParallelProgressIterator, ProgressBar, ProgressStyle};
-use rand::Rng;
-use rayon::iter::IntoParallelIterator;
+use crate::shapes::HitRecord;
+use crate::{ray::Ray, Color, Material, Vec3};
 use rayon::prelude::*;
-use std::fs::File;
-use std::io::{self, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Instant;
 
+use super::adaptive::{AdaptiveSampling, PixelEstimate};
 use super::camera::Camera;
+use super::framebuffer::Framebuffer;
+use super::observer::{CancellationToken, ProgressBarObserver, RenderObserver};
+use super::sampler::{Sampler, SamplerKind};
+use super::tile::Tile;
+
+/// Hit record, material and light index of an object hit by a ray.
+type SceneHit<'a> = (HitRecord, &'a Material, Option<usize>);
 
 pub struct Renderer {
     width: i32,
     height: i32,
     samples_per_pixel: u32,
     max_depth: i32,
-    output: File,
+    seed: u64,
+    sampler: SamplerKind,
+    adaptive: Option<AdaptiveSampling>,
+    tile_size: usize,
+    observer: Box<dyn RenderObserver>,
+    cancellation: CancellationToken,
 }
 
 impl Renderer {
-    pub fn new(
-        width: i32,
-        height: i32,
-        samples_per_pixel: u32,
-        max_depth: i32,
-        output_path: &str,
-    ) -> std::io::Result<Self> {
-        let output = File::create(output_path)?;
-
-        Ok(Self {
+    pub fn new(width: i32, height: i32, samples_per_pixel: u32, max_depth: i32) -> Self {
+        Self {
             width,
             height,
             samples_per_pixel,
             max_depth,
-            output,
-        })
+            seed: 0,
+            sampler: SamplerKind::default(),
+            adaptive: None,
+            tile_size: 32,
+            observer: Box::new(ProgressBarObserver::new()),
+            cancellation: CancellationToken::new(),
+        }
+    }
+
+    /// Set the seed of the random numbers used for rendering. The same seed always
+    /// produces the same image, no matter how the work gets split between threads.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// Set the strategy used to place samples within pixels, on the lens and along paths.
+    pub fn set_sampler(&mut self, sampler: SamplerKind) {
+        self.sampler = sampler;
+    }
+
+    /// Render in passes and stop sampling pixels once they converge. `samples_per_pixel` is
+    /// ignored in favor of the pass size and sample cap of `adaptive`.
+    pub fn set_adaptive_sampling(&mut self, adaptive: AdaptiveSampling) {
+        self.adaptive = Some(adaptive);
+    }
+
+    /// Set the width and height of the square tiles the image is split into for rendering.
+    pub fn set_tile_size(&mut self, tile_size: usize) {
+        self.tile_size = tile_size.max(1);
+    }
+
+    /// Set the observer notified about the render progress. By default a progress bar is
+    /// shown on the terminal; pass `()` to render quietly.
+    pub fn set_observer<O: RenderObserver + 'static>(&mut self, observer: O) {
+        self.observer = Box::new(observer);
+    }
+
+    /// Set the token checked while rendering; cancelling it stops the render early.
+    pub fn set_cancellation_token(&mut self, cancellation: CancellationToken) {
+        self.cancellation = cancellation;
     }
 
-    fn ray_color(r: &Ray, world: &Scene, depth: i32) -> Color {
+    /// Get the renderer's cancellation token.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    /// Path tracing with next event estimation: at every diffuse hit one light is sampled
+    /// directly, and both that and the scattered ray are weighted by multiple importance sampling.
+    fn ray_color(r: &Ray, world: &Scene, depth: i32, sampler: &mut dyn Sampler) -> Color {
+        let mut radiance = Color::new(0.0, 0.0, 0.0);
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = *r;
+        // Density of the scattered direction that produced `ray`, `None` for camera rays and
+        // specular bounces, which light sampling cannot produce.
+        let mut scatter_pdf: Option<f64> = None;
+
         // If we've exceeded the ray bounce limit, no more light is gathered.
-        if depth <= 0 {
-            return Color::new(0.0, 0.0, 0.0);
+        for _ in 0..depth {
+            let (rec, material, light) = match Self::next_event(world, &ray, sampler) {
+                Some(hit) => hit,
+                None => {
+                    radiance += throughput * world.background().color(ray.dir());
+                    break;
+                }
+            };
+
+            let emitted = material.emitted(&ray, &rec);
+            let weight = match (scatter_pdf, light) {
+                (Some(pdf), Some(light)) => {
+                    let light_pdf = world.lights()[light].pdf(ray.orig(), ray.dir())
+                        / world.lights().len() as f64;
+                    Self::power_heuristic(pdf, light_pdf)
+                }
+                _ => 1.0,
+            };
+            radiance += weight * throughput * emitted;
+
+            let wo = -Vec3::unit_vector(ray.dir());
+            radiance +=
+                throughput * Self::sample_light(world, material, &rec, &wo, ray.time(), sampler);
+
+            match material.sample(&wo, &rec, sampler) {
+                Some(sample) => {
+                    scatter_pdf = sample.pdf();
+                    throughput = throughput * sample.weight();
+                    ray = Ray::new(*rec.p(), *sample.wi()).with_time(ray.time());
+                }
+                None => break,
+            }
         }
 
-        if let Some((rec, material)) = world.hit(r, 0.001, std::f64::INFINITY) {
-            if let Some((scattered, attenuation)) = material.scatter(r, &rec) {
-                return attenuation * Self::ray_color(&scattered, world, depth - 1);
-            } else {
-                return Color::new(0.0, 0.0, 0.0);
+        radiance
+    }
+
+    /// Closest place along `ray` where light interacts: a surface or, inside a participating
+    /// medium, a collision at a sampled free-flight distance. Media that the ray crosses
+    /// without a collision are skipped.
+    fn next_event<'a>(
+        world: &'a Scene,
+        ray: &Ray,
+        sampler: &mut dyn Sampler,
+    ) -> Option<SceneHit<'a>> {
+        let mut t_min = 0.001;
+        loop {
+            let (rec, material, index) = world.hit_with_index(ray, t_min, f64::INFINITY)?;
+            let (medium, t_exit) = match rec.medium() {
+                Some(medium) => medium,
+                None => return Some((rec, material, world.object_light(index))),
+            };
+            let grid = world.voxel_grid(index);
+            let t_collision = medium.sample_collision(grid, ray, rec.t(), t_exit, sampler);
+
+            // Surfaces inside the medium block the ray before it can collide behind them
+            let t_end = t_collision.unwrap_or(t_exit);
+            if let Some((rec, material, index)) = world.hit_surface(ray, rec.t(), t_end) {
+                return Some((rec, material, world.object_light(index)));
             }
+            if let Some(t) = t_collision {
+                let p = ray.at(t);
+                let rec = HitRecord::new(p, -Vec3::unit_vector(ray.dir()), t, ray);
+                return Some((rec, material, None));
+            }
+            t_min = t_exit;
+        }
+    }
+
+    /// First object hit by a shadow ray before `t_max`, along with the fraction of light
+    /// transmitted through the participating media in between.
+    fn shadow_hit<'a>(
+        world: &'a Scene,
+        ray: &Ray,
+        t_max: f64,
+        sampler: &mut dyn Sampler,
+    ) -> (Option<SceneHit<'a>>, f64) {
+        let mut transmittance = 1.0;
+        let mut t_min = 0.001;
+        loop {
+            let (rec, material, index) = match world.hit_with_index(ray, t_min, t_max) {
+                Some(hit) => hit,
+                None => return (None, transmittance),
+            };
+            let (medium, t_exit) = match rec.medium() {
+                Some(medium) => medium,
+                None => return (Some((rec, material, world.object_light(index))), transmittance),
+            };
+
+            let grid = world.voxel_grid(index);
+            let surface = world.hit_surface(ray, rec.t(), t_exit);
+            let t_end = surface.as_ref().map_or(t_exit, |(rec, _, _)| rec.t());
+            transmittance *= medium.transmittance(grid, ray, rec.t(), t_end, sampler);
+            if let Some((rec, material, index)) = surface {
+                return (Some((rec, material, world.object_light(index))), transmittance);
+            }
+            t_min = t_exit;
+        }
+    }
+
+    /// Direct light from one randomly picked light source, traced with a shadow ray.
+    fn sample_light(
+        world: &Scene,
+        material: &Material,
+        rec: &HitRecord,
+        wo: &Vec3,
+        time: f64,
+        sampler: &mut dyn Sampler,
+    ) -> Color {
+        let lights = world.lights();
+        if lights.is_empty() {
+            return Color::new(0.0, 0.0, 0.0);
         }
 
-        let unit_dir = Vec3::unit_vector(r.dir());
-        let t = 0.5 * (unit_dir.y() + 1.0);
+        let index = ((sampler.get_1d() * lights.len() as f64) as usize).min(lights.len() - 1);
+        let selection_pdf = 1.0 / lights.len() as f64;
+        let sample = match lights[index].sample(rec.p(), sampler) {
+            Some(sample) => sample,
+            None => return Color::new(0.0, 0.0, 0.0),
+        };
 
-        let start_value = Color::new(1.0, 1.0, 1.0);
-        let end_value = Color::new(0.5, 0.7, 1.0);
+        // Specular materials can't reflect light coming from a single direction
+        let f = material.eval(&sample.wi, wo, rec);
+        if f.near_zero() {
+            return Color::new(0.0, 0.0, 0.0);
+        }
 
-        (1.0 - t) * start_value + t * end_value
+        let shadow_ray = Ray::new(*rec.p(), sample.wi).with_time(time);
+        match (lights[index], sample.pdf) {
+            (Light::Point { intensity, .. }, _) => {
+                let (hit, transmittance) = Self::shadow_hit(world, &shadow_ray, sample.distance, sampler);
+                if hit.is_some() {
+                    return Color::new(0.0, 0.0, 0.0);
+                }
+                let incoming = transmittance * intensity / sample.distance.powf(2.0);
+                (1.0 / selection_pdf) * f * incoming
+            }
+            (_, Some(pdf)) => match Self::shadow_hit(world, &shadow_ray, sample.distance, sampler) {
+                (Some((light_rec, light_material, Some(hit))), transmittance) if hit == index => {
+                    let incoming =
+                        transmittance * light_material.emitted(&shadow_ray, &light_rec);
+                    let light_pdf = selection_pdf * pdf;
+                    let weight =
+                        Self::power_heuristic(light_pdf, material.pdf(&sample.wi, wo, rec));
+                    (weight / light_pdf) * f * incoming
+                }
+                // Occluded by another object
+                _ => Color::new(0.0, 0.0, 0.0),
+            },
+            _ => Color::new(0.0, 0.0, 0.0),
+        }
     }
 
-    fn generate_pixel_color(
+    fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+        let a = pdf * pdf;
+        let b = other_pdf * other_pdf;
+        a / (a + b)
+    }
+
+    /// Radiance carried by sample `index` of the pixel at (`column`, `row`).
+    fn sample_pixel(
         &self,
         column: i32,
         row: i32,
+        index: u32,
         camera: &Camera,
         world: &Scene,
+        sampler: &mut dyn Sampler,
     ) -> Color {
-        let pixels: Vec<_> = (0..self.samples_per_pixel)
-            .into_par_iter()
-            .map(|_| {
-                let mut rng = rand::thread_rng();
+        sampler.start_pixel_sample(column as u32, row as u32, index);
 
-                let u = (column as f64 + rng.gen::<f64>()) / (self.width - 1) as f64;
-                let v = (row as f64 + rng.gen::<f64>()) / (self.height - 1) as f64;
-                let r = camera.get_ray(u, v);
-                Self::ray_color(&r, world, self.max_depth)
+        let (du, dv) = sampler.get_2d();
+        let u = (column as f64 + du) / (self.width - 1) as f64;
+        let v = (row as f64 + dv) / (self.height - 1) as f64;
+        let r = camera.get_ray(u, v, sampler);
+        Self::ray_color(&r, world, self.max_depth, sampler)
+    }
+
+    /// Renders the averaged colors of the pixels of `tile`, row by row, or `None` if the render
+    /// got cancelled in the meantime.
+    fn render_tile(&self, tile: &Tile, camera: &Camera, world: &Scene) -> Option<Vec<Color>> {
+        let mut sampler = self.sampler.create(self.seed, self.samples_per_pixel);
+        let scale = 1.0 / (self.samples_per_pixel as f64);
+
+        tile.pixels()
+            .map(|(x, y)| {
+                if self.cancellation.is_cancelled() {
+                    return None;
+                }
+                let (column, row) = self.pixel_to_camera(x, y);
+                let mut sum = Color::new(0.0, 0.0, 0.0);
+                for sample in 0..self.samples_per_pixel {
+                    sum += self.sample_pixel(column, row, sample, camera, world, sampler.as_mut());
+                }
+                Some(scale * sum)
             })
-            .collect();
+            .collect()
+    }
 
-        pixels
-            .iter()
-            .fold(Color::new(0.0, 0.0, 0.0), |sum, &val| sum + val)
+    /// Camera column and row of an image pixel; image rows count from the top, camera rows
+    /// from the bottom.
+    fn pixel_to_camera(&self, x: usize, y: usize) -> (i32, i32) {
+        (x as i32, self.height - 1 - y as i32)
     }
 
-    fn generate_pixels(&self, camera: &Camera, world: &Scene) -> Vec<Vec<Color>> {
-        let pb = ProgressBar::new(self.height as u64);
-        pb.set_style(ProgressStyle::default_bar().template(
-            "{spinner:.green} {msg} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos}/{len} ({eta_precise})",
-        ));
-        pb.set_message("Generating image pixels");
+    fn generate_pixels(&self, camera: &Camera, world: &Scene, progress: &Progress) -> Framebuffer {
+        let tiles = Tile::grid(self.width as usize, self.height as usize, self.tile_size);
+        progress.set_total(tiles.len());
 
-        let pixels: Vec<Vec<_>> = (0..self.height)
+        let rendered: Vec<_> = tiles
             .into_par_iter()
-            .rev()
-            .progress_with(pb)
-            .map(|j| {
-                (0..self.width)
-                    .into_par_iter()
-                    .map(|i| self.generate_pixel_color(i, j, camera, world))
-                    .collect::<Vec<_>>()
+            .filter_map(|tile| {
+                let pixels = self.render_tile(&tile, camera, world)?;
+                self.observer.tile_finished(&tile, &pixels);
+                progress.advance();
+                Some((tile, pixels))
             })
             .collect();
 
-        // pb.finish_with_message("Successfully generated image pixels");
-
-        pixels
+        let mut image = Framebuffer::new(self.width as usize, self.height as usize);
+        for (tile, pixels) in rendered {
+            image.set_tile(&tile, &pixels);
+        }
+        if !self.cancellation.is_cancelled() {
+            self.observer.pass_finished(0, &image);
+        }
+        image
     }
 
-    fn write_color(&mut self, color: &Color) -> std::io::Result<()> {
-        let mut r = color.x();
-        let mut g = color.y();
-        let mut b = color.z();
+    /// Renders pixels in passes until all of them converged, every pixel reached the sample cap
+    /// or the time budget ran out.
+    fn generate_pixels_adaptive(
+        &self,
+        camera: &Camera,
+        world: &Scene,
+        adaptive: &AdaptiveSampling,
+        progress: &Progress,
+    ) -> Framebuffer {
+        let tiles = Tile::grid(self.width as usize, self.height as usize, self.tile_size);
+        let mut estimates: Vec<Vec<PixelEstimate>> = tiles
+            .iter()
+            .map(|tile| vec![PixelEstimate::default(); tile.len()])
+            .collect();
+        let mut image = Framebuffer::new(self.width as usize, self.height as usize);
+        progress.set_total(tiles.len() * adaptive.passes() as usize);
+
+        for pass in 0..adaptive.passes() {
+            tiles
+                .par_iter()
+                .zip(estimates.par_iter_mut())
+                .for_each(|(tile, estimates)| {
+                    let mut sampler = self.sampler.create(self.seed, adaptive.max_samples());
+                    let mut sampled = false;
+                    for ((x, y), estimate) in tile.pixels().zip(estimates.iter_mut()) {
+                        if self.cancellation.is_cancelled() {
+                            return;
+                        }
+                        if estimate.is_done(adaptive) {
+                            continue;
+                        }
+                        let (column, row) = self.pixel_to_camera(x, y);
+                        for sample in adaptive.pass_range(pass) {
+                            estimate.add(self.sample_pixel(
+                                column,
+                                row,
+                                sample,
+                                camera,
+                                world,
+                                sampler.as_mut(),
+                            ));
+                        }
+                        sampled = true;
+                    }
 
-        let scale = 1.0 / (self.samples_per_pixel as f64);
-        r = (scale * r).sqrt().clamp(0.0, 0.999);
-        g = (scale * g).sqrt().clamp(0.0, 0.999);
-        b = (scale * b).sqrt().clamp(0.0, 0.999);
-
-        let ir = (256.0 * r) as i32;
-        let ig = (256.0 * g) as i32;
-        let ib = (256.0 * b) as i32;
-
-        writeln!(self.output, "{} {} {}", ir, ig, ib)?;
-        Ok(())
-    }
-
-    fn encode_image(&mut self, pixels: &[Vec<Color>]) -> std::io::Result<()> {
-        let pb = ProgressBar::new(self.height as u64);
-        pb.set_style(ProgressStyle::default_bar().template(
-            "{spinner:.green} {msg} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos}/{len} ({eta_precise})",
-        ));
-        pb.set_message("Encoding image");
-
-        // Image header
-        writeln!(&self.output, "P3")?;
-        writeln!(&self.output, "{} {}", self.width, self.height)?;
-        writeln!(&self.output, "255")?;
-
-        // Image data
-        for row in pixels {
-            for pixel_color in row {
-                self.write_color(pixel_color)?;
+                    if sampled {
+                        let pixels: Vec<_> = estimates.iter().map(|e| e.mean()).collect();
+                        self.observer.tile_finished(tile, &pixels);
+                    }
+                    progress.advance();
+                });
+
+            for (tile, estimates) in tiles.iter().zip(&estimates) {
+                let pixels: Vec<_> = estimates.iter().map(|e| e.mean()).collect();
+                image.set_tile(tile, &pixels);
+            }
+            if self.cancellation.is_cancelled() {
+                break;
             }
-            pb.inc(1);
+            self.observer.pass_finished(pass, &image);
+
+            let converged = estimates.iter().flatten().all(|e| e.is_done(adaptive));
+            let out_of_time = adaptive
+                .time_budget()
+                .is_some_and(|budget| progress.elapsed() >= budget);
+            if converged || out_of_time {
+                // The remaining passes are skipped, so they count as done
+                progress.finish();
+                break;
+            }
+        }
+
+        image
+    }
+
+    /// Renders the scene into a framebuffer holding the average radiance of every pixel.
+    ///
+    /// If the render gets cancelled, the image only contains what was finished until then.
+    pub fn render(&self, camera: &Camera, world: &Scene) -> Framebuffer {
+        let progress = Progress::new(self.observer.as_ref());
+        self.observer
+            .render_started(self.width as usize, self.height as usize);
+
+        let image = match &self.adaptive {
+            Some(adaptive) => self.generate_pixels_adaptive(camera, world, adaptive, &progress),
+            None => self.generate_pixels(camera, world, &progress),
+        };
+
+        self.observer
+            .render_finished(progress.elapsed(), self.cancellation.is_cancelled());
+        image
+    }
+}
+
+/// Counts finished units of work, tiles or tile passes, and reports them to the observer.
+struct Progress<'a> {
+    observer: &'a dyn RenderObserver,
+    start: Instant,
+    done: AtomicUsize,
+    total: AtomicUsize,
+}
+
+impl<'a> Progress<'a> {
+    fn new(observer: &'a dyn RenderObserver) -> Self {
+        Self {
+            observer,
+            start: Instant::now(),
+            done: AtomicUsize::new(0),
+            total: AtomicUsize::new(1),
         }
+    }
 
-        pb.finish_with_message("Image encoded");
-        Ok(())
+    fn set_total(&self, total: usize) {
+        self.total.store(total.max(1), Ordering::Relaxed);
     }
 
-    pub fn render(&mut self, camera: &Camera, world: &Scene) -> std::io::Result<()> {
-        let now = Instant::now();
+    fn advance(&self) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        let total = self.total.load(Ordering::Relaxed);
+        self.observer
+            .progress(done as f64 / total as f64, self.start.elapsed());
+    }
+
+    /// Reports all work as done, e.g. when the remaining units are skipped.
+    fn finish(&self) {
+        let total = self.total.load(Ordering::Relaxed);
+        self.done.store(total, Ordering::Relaxed);
+        self.observer.progress(1.0, self.start.elapsed());
+    }
+
+    fn elapsed(&self) -> std::time::Duration {
+        self.start.elapsed()
+    }
+}
 
-        let pixels = self.generate_pixels(camera, world);
-        self.encode_image(&pixels)?;
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::*;
+    use crate::Point3;
+
+    #[derive(Default)]
+    struct LastProgress(Mutex<f64>);
+
+    impl RenderObserver for LastProgress {
+        fn progress(&self, fraction: f64, _elapsed: Duration) {
+            *self.0.lock().unwrap() = fraction;
+        }
+    }
 
-        let elapsed = now.elapsed();
-        println!("Rendering took: {:.2?}", elapsed);
-        io::stdout().flush().unwrap();
+    #[test]
+    fn adaptive_progress_completes_when_converged_early() {
+        let observer = Arc::new(LastProgress::default());
+        let mut renderer = Renderer::new(8, 8, 1, 4);
+        renderer.set_adaptive_sampling(AdaptiveSampling::default());
+        renderer.set_observer(Arc::clone(&observer));
+        let camera = Camera::new(
+            Point3::new(0.0, 0.0, 1.0),
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            1.0,
+            0.0,
+            1.0,
+        );
 
-        Ok(())
+        // An empty scene converges after the first pass
+        renderer.render(&camera, &Scene::default());
+        assert_eq!(*observer.0.lock().unwrap(), 1.0);
     }
 }
@@ -0,0 +1,358 @@
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg32;
+
+/// Source of the random numbers used to render one pixel sample.
+///
+/// Every call to [`Sampler::get_1d`] or [`Sampler::get_2d`] consumes the next dimension of
+/// the current sample, so callers must request values in the same order for all samples
+/// (pixel jitter, lens position, then light and BSDF samples bounce by bounce).
+pub trait Sampler: Send + Sync {
+    /// Restart the sequence for sample `index` of pixel (`x`, `y`).
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32);
+
+    /// Next value in `[0, 1)`.
+    fn get_1d(&mut self) -> f64;
+
+    /// Next pair of values in `[0, 1)`.
+    fn get_2d(&mut self) -> (f64, f64);
+}
+
+/// Sampling strategy used by the renderer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SamplerKind {
+    /// Uniform random numbers.
+    #[default]
+    Independent,
+    /// Jittered samples, one per stratum of every dimension.
+    Stratified,
+    /// Owen-scrambled Halton sequence.
+    Halton,
+    /// Owen-scrambled Sobol sequence.
+    Sobol,
+}
+
+impl SamplerKind {
+    /// Creates a sampler of this kind for images rendered with `samples_per_pixel` samples.
+    pub fn create(&self, seed: u64, samples_per_pixel: u32) -> Box<dyn Sampler> {
+        match self {
+            SamplerKind::Independent => Box::new(IndependentSampler::new(seed)),
+            SamplerKind::Stratified => Box::new(StratifiedSampler::new(seed, samples_per_pixel)),
+            SamplerKind::Halton => Box::new(HaltonSampler::new(seed)),
+            SamplerKind::Sobol => Box::new(SobolSampler::new(seed)),
+        }
+    }
+}
+
+/// SplitMix64 finalizer
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+fn hash(values: &[u64]) -> u64 {
+    values.iter().fold(0x9e3779b97f4a7c15, |h, &v| mix(h ^ v))
+}
+
+/// Random number generator seeded only from the render seed and the sample's position, so
+/// that results do not depend on which thread renders the sample.
+fn sample_rng(seed: u64, x: u32, y: u32, index: u32) -> Pcg32 {
+    Pcg32::seed_from_u64(hash(&[seed, (y as u64) << 32 | x as u64, index as u64]))
+}
+
+fn to_unit_float(bits: u32) -> f64 {
+    bits as f64 / (1u64 << 32) as f64
+}
+
+pub struct IndependentSampler {
+    seed: u64,
+    rng: Pcg32,
+}
+
+impl IndependentSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: sample_rng(seed, 0, 0, 0),
+        }
+    }
+}
+
+impl Sampler for IndependentSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.rng = sample_rng(self.seed, x, y, index);
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        self.rng.gen()
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.rng.gen(), self.rng.gen())
+    }
+}
+
+pub struct StratifiedSampler {
+    seed: u64,
+    samples_per_pixel: u32,
+    x_strata: u32,
+    y_strata: u32,
+    rng: Pcg32,
+    pixel: u64,
+    index: u32,
+    dimension: u64,
+}
+
+impl StratifiedSampler {
+    pub fn new(seed: u64, samples_per_pixel: u32) -> Self {
+        let samples_per_pixel = samples_per_pixel.max(1);
+        let x_strata = (samples_per_pixel as f64).sqrt().ceil() as u32;
+        let y_strata = samples_per_pixel.div_ceil(x_strata);
+        Self {
+            seed,
+            samples_per_pixel,
+            x_strata,
+            y_strata,
+            rng: sample_rng(seed, 0, 0, 0),
+            pixel: 0,
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    /// Stratum of the current sample in a dimension with `count` strata. Strata are shuffled
+    /// differently for every pixel and dimension so that dimensions stay uncorrelated.
+    fn stratum(&mut self, count: u32) -> u32 {
+        let shuffle = hash(&[self.seed, self.pixel, self.dimension]);
+        self.dimension += 1;
+        permutation_element(self.index % count, count, shuffle as u32)
+    }
+}
+
+impl Sampler for StratifiedSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.rng = sample_rng(self.seed, x, y, index);
+        self.pixel = (y as u64) << 32 | x as u64;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        let stratum = self.stratum(self.samples_per_pixel);
+        (stratum as f64 + self.rng.gen::<f64>()) / self.samples_per_pixel as f64
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let cell = self.stratum(self.x_strata * self.y_strata);
+        let x = cell % self.x_strata;
+        let y = cell / self.x_strata;
+        (
+            (x as f64 + self.rng.gen::<f64>()) / self.x_strata as f64,
+            (y as f64 + self.rng.gen::<f64>()) / self.y_strata as f64,
+        )
+    }
+}
+
+/// Element `i` of a random permutation of `0..l` picked by `p` (Kensler, "Correlated
+/// Multi-Jittered Sampling").
+fn permutation_element(mut i: u32, l: u32, p: u32) -> u32 {
+    let mut w = l - 1;
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+    loop {
+        i ^= p;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= p >> 16;
+        i ^= (i & w) >> 4;
+        i ^= p >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= p >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | p >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < l {
+            break;
+        }
+    }
+    (i.wrapping_add(p)) % l
+}
+
+const PRIMES: [u32; 32] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+    101, 103, 107, 109, 113, 127, 131,
+];
+
+pub struct HaltonSampler {
+    seed: u64,
+    rng: Pcg32,
+    pixel: u64,
+    index: u32,
+    dimension: usize,
+}
+
+impl HaltonSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: sample_rng(seed, 0, 0, 0),
+            pixel: 0,
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    /// Radical inverse of `index` with Owen scrambling: every digit is permuted depending
+    /// on the digits before it.
+    fn owen_scrambled_radical_inverse(base: u32, mut index: u32, seed: u64) -> f64 {
+        let inv_base = 1.0 / base as f64;
+        let mut inv_base_m = 1.0;
+        let mut value = 0.0;
+        // Digits seen so far, only used to seed the permutation of the next digit
+        let mut prefix = 0u64;
+        let mut depth = 0u64;
+        // Continue past the last non-zero digit until the digits no longer change the result
+        while 1.0 - (base - 1) as f64 * inv_base_m < 1.0 {
+            let next = index / base;
+            let digit = index - next * base;
+            // The depth is part of the seed, otherwise all-zero prefixes of different lengths
+            // would share a permutation
+            let digit_seed = hash(&[seed, depth, prefix]) as u32;
+            let digit = permutation_element(digit, base, digit_seed);
+            prefix = prefix.wrapping_mul(base as u64).wrapping_add(digit as u64);
+            inv_base_m *= inv_base;
+            value += digit as f64 * inv_base_m;
+            index = next;
+            depth += 1;
+        }
+        value.min(1.0 - f64::EPSILON)
+    }
+
+    fn next(&mut self) -> f64 {
+        let dimension = self.dimension;
+        self.dimension += 1;
+
+        match PRIMES.get(dimension) {
+            Some(&base) => {
+                // Scrambling differently for every pixel decorrelates neighbouring pixels
+                let seed = hash(&[self.seed, self.pixel, dimension as u64]);
+                Self::owen_scrambled_radical_inverse(base, self.index, seed)
+            }
+            // Too many dimensions for well distributed Halton points
+            None => self.rng.gen(),
+        }
+    }
+}
+
+impl Sampler for HaltonSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.rng = sample_rng(self.seed, x, y, index);
+        self.pixel = (y as u64) << 32 | x as u64;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        self.next()
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        (self.next(), self.next())
+    }
+}
+
+/// Owen-scrambled Sobol points (Burley, "Practical Hash-based Owen Scrambling").
+///
+/// Only the first two Sobol dimensions are used; higher dimensions are padded with
+/// independently shuffled and scrambled copies of them.
+pub struct SobolSampler {
+    seed: u64,
+    pixel: u64,
+    index: u32,
+    dimension: u64,
+}
+
+impl SobolSampler {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            pixel: 0,
+            index: 0,
+            dimension: 0,
+        }
+    }
+
+    fn sobol(index: u32, dimension: u32) -> u32 {
+        if dimension == 0 {
+            return index.reverse_bits();
+        }
+
+        // Second Sobol dimension, its direction numbers are v[i] = v[i-1] ^ (v[i-1] >> 1)
+        let mut result = 0;
+        let mut direction = 1u32 << 31;
+        let mut index = index;
+        while index != 0 {
+            if index & 1 != 0 {
+                result ^= direction;
+            }
+            index >>= 1;
+            direction ^= direction >> 1;
+        }
+        result
+    }
+
+    fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+        x = x.wrapping_add(seed);
+        x ^= x.wrapping_mul(0x6c50b47c);
+        x ^= x.wrapping_mul(0xb82f1e52);
+        x ^= x.wrapping_mul(0xc7afe638);
+        x ^= x.wrapping_mul(0x8d22f6e6);
+        x
+    }
+
+    fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+        Self::laine_karras_permutation(x.reverse_bits(), seed).reverse_bits()
+    }
+
+    fn next(&mut self, count: u32) -> [f64; 2] {
+        let seed = hash(&[self.seed, self.pixel, self.dimension]);
+        self.dimension += 1;
+
+        let index = Self::nested_uniform_scramble(self.index, seed as u32);
+        let mut values = [0.0; 2];
+        for (dimension, value) in values.iter_mut().enumerate().take(count as usize) {
+            let scramble = mix(seed ^ (dimension as u64 + 1)) as u32;
+            let bits =
+                Self::nested_uniform_scramble(Self::sobol(index, dimension as u32), scramble);
+            *value = to_unit_float(bits);
+        }
+        values
+    }
+}
+
+impl Sampler for SobolSampler {
+    fn start_pixel_sample(&mut self, x: u32, y: u32, index: u32) {
+        self.pixel = (y as u64) << 32 | x as u64;
+        self.index = index;
+        self.dimension = 0;
+    }
+
+    fn get_1d(&mut self) -> f64 {
+        self.next(1)[0]
+    }
+
+    fn get_2d(&mut self) -> (f64, f64) {
+        let [u, v] = self.next(2);
+        (u, v)
+    }
+}
@@ -0,0 +1,69 @@
+/// Rectangular block of pixels rendered as one unit of work.
+///
+/// Coordinates are in pixels, with rows counted from the top like in [`super::framebuffer::Framebuffer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Tile {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Splits an image into tiles of at most `size` by `size` pixels, row by row from the top.
+    pub fn grid(width: usize, height: usize, size: usize) -> Vec<Tile> {
+        let size = size.max(1);
+        (0..height)
+            .step_by(size)
+            .flat_map(|y| {
+                (0..width)
+                    .step_by(size)
+                    .map(move |x| Tile::new(x, y, size.min(width - x), size.min(height - y)))
+            })
+            .collect()
+    }
+
+    /// Get the tile's left column.
+    pub fn x(&self) -> usize {
+        self.x
+    }
+
+    /// Get the tile's top row.
+    pub fn y(&self) -> usize {
+        self.y
+    }
+
+    /// Get the tile's width.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the tile's height.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Number of pixels in the tile.
+    pub fn len(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over the image coordinates `(x, y)` of the tile's pixels, row by row.
+    pub fn pixels(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (self.y..self.y + self.height)
+            .flat_map(move |y| (self.x..self.x + self.width).map(move |x| (x, y)))
+    }
+}
@@ -0,0 +1,161 @@
+use crate::Color;
+
+/// Operator compressing scene radiance into the displayable `[0, 1]` range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToneMapOperator {
+    /// Cut off everything above 1.
+    Clamp,
+    /// `L / (1 + L)` applied to luminance.
+    Reinhard,
+    /// Reinhard with a white point: luminance `white` and above maps to 1. Without a positive
+    /// white point it is the same as [`ToneMapOperator::Reinhard`].
+    ExtendedReinhard { white: f64 },
+    /// Narkowicz's fit of the ACES filmic curve.
+    Aces,
+}
+
+/// Transfer function used to encode display values into 8 bits.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransferFunction {
+    /// Piecewise sRGB curve, what most image viewers expect.
+    Srgb,
+    /// Plain power curve `c^(1 / gamma)`.
+    Gamma(f64),
+}
+
+/// Post-process turning the linear framebuffer into 8-bit display values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToneMapping {
+    operator: ToneMapOperator,
+    exposure: f64,
+    transfer: TransferFunction,
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        Self {
+            operator: ToneMapOperator::Clamp,
+            exposure: 0.0,
+            transfer: TransferFunction::Srgb,
+        }
+    }
+}
+
+impl ToneMapping {
+    pub fn new(operator: ToneMapOperator, exposure: f64, transfer: TransferFunction) -> Self {
+        Self {
+            operator,
+            exposure,
+            transfer,
+        }
+    }
+
+    pub fn with_operator(mut self, operator: ToneMapOperator) -> Self {
+        self.operator = operator;
+        self
+    }
+
+    /// Set exposure compensation in EV stops; every stop doubles the brightness.
+    pub fn with_exposure(mut self, exposure: f64) -> Self {
+        self.exposure = exposure;
+        self
+    }
+
+    pub fn with_transfer(mut self, transfer: TransferFunction) -> Self {
+        self.transfer = transfer;
+        self
+    }
+
+    /// Get the tone mapping's operator.
+    pub fn operator(&self) -> ToneMapOperator {
+        self.operator
+    }
+
+    /// Get the tone mapping's exposure.
+    pub fn exposure(&self) -> f64 {
+        self.exposure
+    }
+
+    /// Get the tone mapping's transfer function.
+    pub fn transfer(&self) -> TransferFunction {
+        self.transfer
+    }
+
+    /// Maps linear radiance to display values in `[0, 1]`, before transfer encoding.
+    pub fn tone_map(&self, color: &Color) -> Color {
+        let exposed = 2f64.powf(self.exposure) * *color;
+        let c = Color::new(
+            exposed.x().max(0.0),
+            exposed.y().max(0.0),
+            exposed.z().max(0.0),
+        );
+
+        let mapped = match self.operator {
+            ToneMapOperator::Clamp => c,
+            ToneMapOperator::ExtendedReinhard { white } if white > 0.0 => {
+                Self::scale_luminance(&c, |l| l * (1.0 + l / (white * white)) / (1.0 + l))
+            }
+            ToneMapOperator::Reinhard | ToneMapOperator::ExtendedReinhard { .. } => {
+                Self::scale_luminance(&c, |l| l / (1.0 + l))
+            }
+            ToneMapOperator::Aces => {
+                let aces = |x: f64| (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14);
+                Color::new(aces(c.x()), aces(c.y()), aces(c.z()))
+            }
+        };
+
+        Color::new(
+            mapped.x().clamp(0.0, 1.0),
+            mapped.y().clamp(0.0, 1.0),
+            mapped.z().clamp(0.0, 1.0),
+        )
+    }
+
+    /// Tone maps and encodes linear radiance into 8-bit RGB.
+    pub fn encode(&self, color: &Color) -> [u8; 3] {
+        let c = self.tone_map(color);
+        let transfer = |v: f64| {
+            let v = match self.transfer {
+                TransferFunction::Srgb => {
+                    if v <= 0.0031308 {
+                        12.92 * v
+                    } else {
+                        1.055 * v.powf(1.0 / 2.4) - 0.055
+                    }
+                }
+                TransferFunction::Gamma(gamma) => v.powf(1.0 / gamma),
+            };
+            (255.0 * v + 0.5).clamp(0.0, 255.0) as u8
+        };
+        [transfer(c.x()), transfer(c.y()), transfer(c.z())]
+    }
+
+    fn scale_luminance<F: Fn(f64) -> f64>(c: &Color, f: F) -> Color {
+        let luminance = c.luminance();
+        if luminance <= 0.0 {
+            return *c;
+        }
+        (f(luminance) / luminance) * *c
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extended_reinhard_without_white_point_is_reinhard() {
+        let color = Color::new(2.0, 0.5, 0.25);
+        let reinhard = ToneMapping::default()
+            .with_operator(ToneMapOperator::Reinhard)
+            .tone_map(&color);
+        for white in [0.0, -1.0, f64::NAN] {
+            let extended = ToneMapping::default()
+                .with_operator(ToneMapOperator::ExtendedReinhard { white })
+                .tone_map(&color);
+            for axis in 0..3 {
+                assert_eq!(extended[axis], reinhard[axis]);
+            }
+        }
+    }
+}
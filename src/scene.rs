@@ -1,30 +1,300 @@
-use crate::{ray::Ray, shapes::{HitRecord, Hittable}, Material, Object, Shape};
+use std::sync::OnceLock;
+
+use crate::{
+    background::Background,
+    bvh::Bvh,
+    light::Light,
+    ray::Ray,
+    shapes::{Aabb, HitRecord, Hittable},
+    Color, Material, Object, Point3, Shape, Transform, Vec3, VoxelGrid,
+};
 
 #[derive(Default)]
 pub struct Scene {
     objects: Vec<Object>,
+    // Built lazily on the first `hit` and dropped whenever the object list changes.
+    bvh: OnceLock<Bvh>,
+    background: Background,
+    lights: Vec<Light>,
+    // Index into `lights` for every object that is also sampled as a light.
+    object_lights: Vec<Option<usize>>,
 }
 
 impl Scene {
+    /// Adds an object. Emissive spheres and quads are also registered as lights, so that the
+    /// renderer can sample them directly. So are instances of them, unless a sphere gets
+    /// stretched into an ellipsoid.
     pub fn add(&mut self, shape: Shape, material: Material) {
+        let light = match material {
+            Material::DiffuseLight { .. } => Self::surface_light(&shape, &Transform::identity()),
+            _ => None,
+        };
+        let light = light.map(|light| {
+            self.lights.push(light);
+            self.lights.len() - 1
+        });
+
         self.objects.push(Object::new(shape, material));
+        self.object_lights.push(light);
+        self.bvh = OnceLock::new();
+    }
+
+    /// Light covering `shape` moved by `transform`, if it can be sampled directly.
+    fn surface_light(shape: &Shape, transform: &Transform) -> Option<Light> {
+        match shape {
+            Shape::Sphere { center, radius } => Some(Light::Sphere {
+                center: transform.point(center),
+                radius: radius * transform.uniform_scale()?,
+            }),
+            Shape::Quad { origin, u, v } => {
+                let (tu, tv) = (transform.vector(u), transform.vector(v));
+                // Mirroring flips `u × v`; swap the edges to keep emitting from the front
+                let front = transform.normal(&Vec3::cross(u, v));
+                let (u, v) = if Vec3::dot(&Vec3::cross(&tu, &tv), &front) < 0.0 {
+                    (tv, tu)
+                } else {
+                    (tu, tv)
+                };
+                Some(Light::Quad {
+                    origin: transform.point(origin),
+                    u,
+                    v,
+                })
+            }
+            Shape::Instance {
+                shape,
+                transform: inner,
+            } => Self::surface_light(shape, &inner.then(transform)),
+            _ => None,
+        }
+    }
+
+    pub fn add_point_light(&mut self, position: Point3, intensity: Color) {
+        self.lights.push(Light::Point {
+            position,
+            intensity,
+        });
     }
 
     pub fn clear(&mut self) {
         self.objects.clear();
+        self.lights.clear();
+        self.object_lights.clear();
+        self.bvh = OnceLock::new();
+    }
+
+    /// Get a reference to the scene's lights.
+    pub fn lights(&self) -> &[Light] {
+        &self.lights
     }
 
-    pub fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(HitRecord, Material)> {
-        let mut result = None;
-        let mut closest_so_far = t_max;
+    pub fn set_background(&mut self, background: Background) {
+        self.background = background;
+    }
 
-        for object in &self.objects {
-            if let Some(rec) = object.shape().hit(r, t_min, closest_so_far) {
-                closest_so_far = rec.t();
-                result = Some((rec, *object.material()));
-            }
+    /// Get a reference to the scene's background.
+    pub fn background(&self) -> &Background {
+        &self.background
+    }
+
+    fn bvh(&self) -> &Bvh {
+        self.bvh.get_or_init(|| {
+            let bounds: Vec<_> = self
+                .objects
+                .iter()
+                .map(|object| object.shape().bounding_box())
+                .collect();
+            Bvh::new(&bounds)
+        })
+    }
+
+    /// Bounds of all objects in the scene, or `None` if it is empty.
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh().bounds()
+    }
+
+    pub fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<(HitRecord, &Material)> {
+        self.hit_with_index(r, t_min, t_max)
+            .map(|(rec, material, _)| (rec, material))
+    }
+
+    /// Like [`Scene::hit`], but also returns the index of the object that was hit.
+    pub(crate) fn hit_with_index(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<(HitRecord, &Material, usize)> {
+        self.hit_filtered(r, t_min, t_max, |_| true)
+    }
+
+    /// Like [`Scene::hit_with_index`], but sees through participating media.
+    pub(crate) fn hit_surface(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<(HitRecord, &Material, usize)> {
+        self.hit_filtered(r, t_min, t_max, |rec| rec.medium().is_none())
+    }
+
+    fn hit_filtered<F>(
+        &self,
+        r: &Ray,
+        t_min: f64,
+        t_max: f64,
+        filter: F,
+    ) -> Option<(HitRecord, &Material, usize)>
+    where
+        F: Fn(&HitRecord) -> bool,
+    {
+        self.bvh()
+            .hit(r, t_min, t_max, |index, r, t_min, t_max| {
+                self.objects[index]
+                    .shape()
+                    .hit(r, t_min, t_max)
+                    .filter(|rec| filter(rec))
+            })
+            .map(|(index, rec)| (rec, self.objects[index].material(), index))
+    }
+
+    /// Index into [`Scene::lights`] of object `index`, if it is also sampled as a light.
+    pub(crate) fn object_light(&self, index: usize) -> Option<usize> {
+        self.object_lights[index]
+    }
+
+    /// Voxel grid of object `index`, needed to evaluate the `Grid` media in its hits.
+    pub(crate) fn voxel_grid(&self, index: usize) -> Option<&VoxelGrid> {
+        self.objects[index].shape().voxel_grid()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn hollow_sphere_inside_glass_is_hit() {
+        let glass = Material::Dielectric { ir: 1.5 };
+        let mut scene = Scene::default();
+        scene.add(
+            Shape::Sphere {
+                center: Point3::new(0.0, 0.0, 0.0),
+                radius: 1.0,
+            },
+            glass.clone(),
+        );
+        scene.add(
+            Shape::Sphere {
+                center: Point3::new(0.5, 0.0, 0.0),
+                radius: -0.4,
+            },
+            glass.clone(),
+        );
+        // Enough other objects that the BVH gets split
+        for i in 0..16 {
+            scene.add(
+                Shape::Sphere {
+                    center: Point3::new(3.0 + 2.0 * i as f64, 0.0, 0.0),
+                    radius: 0.5,
+                },
+                glass.clone(),
+            );
         }
 
-        result
+        // Starts inside the glass, so the first hit must be the bubble
+        let r = Ray::new(Point3::new(0.5, 0.0, 0.6), Vec3::new(0.0, 0.0, -1.0));
+        let (rec, _) = scene
+            .hit(&r, 0.001, f64::INFINITY)
+            .expect("bubble was culled");
+        assert!((rec.t() - 0.2).abs() < 1e-9);
+        assert!(!rec.front_face());
+    }
+
+    #[test]
+    fn hit_surface_sees_through_instanced_media() {
+        let fog = Shape::ConstantMedium {
+            boundary: Arc::new(Shape::Sphere {
+                center: Point3::new(0.0, 0.0, 0.0),
+                radius: 2.0,
+            }),
+            density: 0.5,
+        };
+        let mut scene = Scene::default();
+        scene.add(
+            Shape::Instance {
+                shape: Arc::new(fog),
+                transform: Arc::new(Transform::translation(Vec3::new(1.0, 0.0, 0.0))),
+            },
+            Material::Isotropic {
+                albedo: Color::new(1.0, 1.0, 1.0).into(),
+            },
+        );
+        scene.add(
+            Shape::Sphere {
+                center: Point3::new(1.0, 0.0, 0.0),
+                radius: 0.5,
+            },
+            Material::Lambertian {
+                albedo: Color::new(0.5, 0.5, 0.5).into(),
+            },
+        );
+
+        let r = Ray::new(Point3::new(1.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+        let (rec, _) = scene.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert!(rec.medium().is_some());
+        assert!((rec.t() - 3.0).abs() < 1e-9);
+
+        let (rec, _, _) = scene.hit_surface(&r, 0.001, f64::INFINITY).unwrap();
+        assert!(rec.medium().is_none());
+        assert!((rec.t() - 4.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn instanced_lights_are_registered() {
+        let light = Material::DiffuseLight {
+            emit: Color::new(4.0, 4.0, 4.0),
+        };
+        let instance = |shape: Shape, transform: Transform| Shape::Instance {
+            shape: Arc::new(shape),
+            transform: Arc::new(transform),
+        };
+        let sphere = || Shape::Sphere {
+            center: Point3::new(1.0, 0.0, 0.0),
+            radius: 0.5,
+        };
+
+        let mut scene = Scene::default();
+        let moved = Transform::scaling(Vec3::new(2.0, 2.0, 2.0))
+            .then(&Transform::rotation_y(30.0))
+            .then(&Transform::translation(Vec3::new(0.0, 3.0, 0.0)));
+        scene.add(instance(sphere(), moved), light.clone());
+        let mirrored = Transform::scaling(Vec3::new(1.0, 1.0, -1.0));
+        scene.add(
+            instance(Shape::xy_rect(0.0, 1.0, 0.0, 1.0, 2.0), mirrored),
+            light.clone(),
+        );
+        let stretched = Transform::scaling(Vec3::new(1.0, 2.0, 1.0));
+        scene.add(instance(sphere(), stretched), light);
+
+        assert_eq!(scene.lights().len(), 2);
+        match scene.lights()[0] {
+            Light::Sphere { center, radius } => {
+                let expected = moved.point(&Point3::new(1.0, 0.0, 0.0));
+                assert!((center - expected).len() < 1e-9);
+                assert!((radius - 1.0).abs() < 1e-9);
+            }
+            light => panic!("expected a sphere light, found {:?}", light),
+        }
+        match scene.lights()[1] {
+            Light::Quad { origin, u, v } => {
+                assert!((origin.z() + 2.0).abs() < 1e-9);
+                // Still facing away from the mirror plane, like the mirrored quad itself
+                assert!(Vec3::cross(&u, &v).z() < 0.0);
+            }
+            light => panic!("expected a quad light, found {:?}", light),
+        }
     }
 }
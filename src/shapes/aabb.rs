@@ -0,0 +1,124 @@
+use crate::{ray::Ray, Point3, Vec3};
+
+/// Axis-aligned bounding box described by its minimum and maximum corners.
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb {
+    min: Point3,
+    max: Point3,
+}
+
+impl Aabb {
+    pub fn new(min: Point3, max: Point3) -> Self {
+        Self { min, max }
+    }
+
+    /// Box spanned by two arbitrary corner points.
+    pub fn from_points(a: &Point3, b: &Point3) -> Self {
+        Self::new(
+            Point3::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z())),
+            Point3::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z())),
+        )
+    }
+
+    /// Smallest box enclosing both `a` and `b`.
+    pub fn surrounding(a: &Aabb, b: &Aabb) -> Aabb {
+        Aabb::new(
+            Point3::new(
+                a.min.x().min(b.min.x()),
+                a.min.y().min(b.min.y()),
+                a.min.z().min(b.min.z()),
+            ),
+            Point3::new(
+                a.max.x().max(b.max.x()),
+                a.max.y().max(b.max.y()),
+                a.max.z().max(b.max.z()),
+            ),
+        )
+    }
+
+    /// Grow the box so that it also encloses `p`.
+    pub fn expand(&mut self, p: &Point3) {
+        for a in 0..3 {
+            self.min[a] = self.min[a].min(p[a]);
+            self.max[a] = self.max[a].max(p[a]);
+        }
+    }
+
+    /// Copy of the box where every side is at least `delta` wide, so that flat shapes
+    /// still have a volume the slab test can hit.
+    pub fn pad(&self, delta: f64) -> Aabb {
+        let mut padded = *self;
+        for a in 0..3 {
+            let size = self.max[a] - self.min[a];
+            if size < delta {
+                let half = 0.5 * (delta - size);
+                padded.min[a] -= half;
+                padded.max[a] += half;
+            }
+        }
+        padded
+    }
+
+    /// Slab test: returns whether the ray enters the box within `[t_min, t_max]`.
+    pub fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.interval(r, t_min, t_max).is_some()
+    }
+
+    /// Slab test: part of `[t_min, t_max]` in which the ray is inside the box, or `None` if
+    /// it misses the box in that range.
+    pub fn interval(&self, r: &Ray, mut t_min: f64, mut t_max: f64) -> Option<(f64, f64)> {
+        for a in 0..3 {
+            let inv_d = 1.0 / r.dir()[a];
+            let mut t0 = (self.min[a] - r.orig()[a]) * inv_d;
+            let mut t1 = (self.max[a] - r.orig()[a]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
+            if t_max < t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let d = self.diagonal();
+        2.0 * (d.x() * d.y() + d.y() * d.z() + d.z() * d.x())
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        0.5 * (self.min + self.max)
+    }
+
+    pub fn diagonal(&self) -> Vec3 {
+        self.max - self.min
+    }
+
+    /// Index of the axis along which the box is the widest.
+    pub fn longest_axis(&self) -> usize {
+        let d = self.diagonal();
+        if d.x() > d.y() && d.x() > d.z() {
+            0
+        } else if d.y() > d.z() {
+            1
+        } else {
+            2
+        }
+    }
+
+    pub fn contains(&self, p: &Point3) -> bool {
+        (0..3).all(|a| self.min[a] <= p[a] && p[a] <= self.max[a])
+    }
+
+    /// Get a reference to the box's min corner.
+    pub fn min(&self) -> &Point3 {
+        &self.min
+    }
+
+    /// Get a reference to the box's max corner.
+    pub fn max(&self) -> &Point3 {
+        &self.max
+    }
+}
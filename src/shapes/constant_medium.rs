@@ -0,0 +1,57 @@
+use crate::{ray::Ray, Medium, Vec3};
+
+use super::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    shape::Shape,
+};
+
+/// Participating medium of uniform density filling a convex boundary shape.
+///
+/// A hit marks where the ray enters the medium. Where it actually scatters is left to the
+/// renderer, which samples a free-flight distance up to the exit recorded in the hit.
+pub struct ConstantMedium<'a> {
+    boundary: &'a Shape,
+    density: f64,
+}
+
+impl<'a> ConstantMedium<'a> {
+    pub fn new(boundary: &'a Shape, density: f64) -> Self {
+        Self { boundary, density }
+    }
+}
+
+impl Hittable for ConstantMedium<'_> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t_enter, t_exit) = interval(self.boundary, r, t_min, t_max)?;
+        let medium = Medium::Homogeneous {
+            density: self.density,
+        };
+        Some(entry_record(r, t_enter).with_medium(medium, t_exit))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.boundary.bounding_box()
+    }
+}
+
+/// Part of `[t_min, t_max]` in which the ray is inside the convex `boundary`, which may also
+/// contain the ray origin.
+pub(super) fn interval(
+    boundary: &dyn Hittable,
+    r: &Ray,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, f64)> {
+    let entry = boundary.hit(r, f64::NEG_INFINITY, f64::INFINITY)?;
+    let exit = boundary.hit(r, entry.t() + 0.0001, f64::INFINITY)?;
+
+    let t_enter = entry.t().max(t_min);
+    let t_exit = exit.t().min(t_max);
+    (t_enter < t_exit).then_some((t_enter, t_exit))
+}
+
+/// Hit where the ray enters a medium. Media have no surface, so the normal is arbitrary.
+pub(super) fn entry_record(r: &Ray, t: f64) -> HitRecord {
+    HitRecord::new(r.at(t), -Vec3::unit_vector(r.dir()), t, r)
+}
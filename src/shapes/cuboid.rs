@@ -0,0 +1,58 @@
+use crate::{ray::Ray, Point3, Vec3};
+
+use super::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    quad::Quad,
+};
+
+/// Axis-aligned box made of six quads facing outwards.
+pub struct Cuboid {
+    min: Point3,
+    max: Point3,
+}
+
+impl Cuboid {
+    /// Creates the box spanned by two opposite corners.
+    pub fn new(a: Point3, b: Point3) -> Self {
+        let min = Point3::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z()));
+        let max = Point3::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z()));
+        Self { min, max }
+    }
+
+    /// The sides in the order front (+Z), right (+X), back (-Z), left (-X), top (+Y) and
+    /// bottom (-Y).
+    pub fn sides(&self) -> [Quad; 6] {
+        let (min, max) = (self.min, self.max);
+        let dx = Vec3::new(max.x() - min.x(), 0.0, 0.0);
+        let dy = Vec3::new(0.0, max.y() - min.y(), 0.0);
+        let dz = Vec3::new(0.0, 0.0, max.z() - min.z());
+
+        [
+            Quad::new(Point3::new(min.x(), min.y(), max.z()), dx, dy),
+            Quad::new(Point3::new(max.x(), min.y(), max.z()), -dz, dy),
+            Quad::new(Point3::new(max.x(), min.y(), min.z()), -dx, dy),
+            Quad::new(Point3::new(min.x(), min.y(), min.z()), dz, dy),
+            Quad::new(Point3::new(min.x(), max.y(), max.z()), dx, -dz),
+            Quad::new(Point3::new(min.x(), min.y(), min.z()), dx, dz),
+        ]
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let mut closest = None;
+        let mut closest_so_far = t_max;
+        for side in self.sides() {
+            if let Some(rec) = side.hit(r, t_min, closest_so_far) {
+                closest_so_far = rec.t();
+                closest = Some(rec);
+            }
+        }
+        closest
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::new(self.min, self.max).pad(1e-4)
+    }
+}
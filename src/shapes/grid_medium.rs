@@ -0,0 +1,76 @@
+use crate::{ray::Ray, Medium, Point3, Vec3};
+
+use super::{
+    aabb::Aabb,
+    constant_medium::entry_record,
+    hittable::{HitRecord, Hittable},
+};
+
+/// Participating medium whose density comes from a voxel grid stretched over an axis-aligned
+/// box. Like [`super::constant_medium::ConstantMedium`], its hits only mark where the ray
+/// enters the volume; the grid itself is looked up by the renderer.
+pub struct GridMedium {
+    bounds: Aabb,
+    density: f64,
+}
+
+impl GridMedium {
+    pub fn new(a: Point3, b: Point3, density: f64) -> Self {
+        Self {
+            bounds: Aabb::from_points(&a, &b),
+            density,
+        }
+    }
+}
+
+impl Hittable for GridMedium {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (min, max) = (*self.bounds.min(), *self.bounds.max());
+        let size = max - min;
+        // Flat boxes can't hold a volume
+        if size.x() <= 0.0 || size.y() <= 0.0 || size.z() <= 0.0 {
+            return None;
+        }
+
+        // Starts at `t_min` when the ray origin is inside the box
+        let (t_enter, t_exit) = self.bounds.interval(r, t_min, t_max)?;
+        if t_enter >= t_exit {
+            return None;
+        }
+
+        let to_grid = |v: Vec3| Vec3::new(v.x() / size.x(), v.y() / size.y(), v.z() / size.z());
+        let medium = Medium::Grid {
+            density: self.density,
+            origin: to_grid(*r.orig() - min),
+            direction: to_grid(*r.dir()),
+        };
+        Some(entry_record(r, t_enter).with_medium(medium, t_exit))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ray_starting_inside_enters_at_t_min() {
+        let medium = GridMedium::new(Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 2.0, 2.0), 1.0);
+        let r = Ray::new(Point3::new(1.0, 1.0, 1.0), Vec3::new(0.5, 0.0, 0.0));
+
+        let rec = medium.hit(&r, 0.001, f64::INFINITY).unwrap();
+        let (_, t_exit) = rec.medium().unwrap();
+        assert!((rec.t() - 0.001).abs() < 1e-12);
+        assert!((t_exit - 2.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn flat_box_is_never_hit() {
+        let medium = GridMedium::new(Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 0.0, 2.0), 1.0);
+        let r = Ray::new(Point3::new(1.0, -1.0, 1.0), Vec3::new(0.0, 1.0, 0.0));
+        assert!(medium.hit(&r, 0.001, f64::INFINITY).is_none());
+    }
+}
@@ -1,10 +1,22 @@
-use crate::{Point3, Vec3, ray::Ray};
+use crate::{Medium, Point3, Transform, Vec3, ray::Ray};
+
+use super::aabb::Aabb;
 
 pub struct HitRecord {
     p: Point3,
+    // Shading normal, possibly perturbed, and the true normal of the surface. Both point
+    // against the incoming ray.
     normal: Vec3,
+    geometric_normal: Vec3,
+    // Partial derivatives of the position with respect to the surface coordinates.
+    dpdu: Vec3,
+    dpdv: Vec3,
     t: f64,
     front_face: bool,
+    barycentric: (f64, f64),
+    uv: (f64, f64),
+    // Medium the ray entered and `t` at which it leaves it again.
+    medium: Option<(Medium, f64)>,
 }
 
 impl HitRecord {
@@ -20,24 +32,107 @@ impl HitRecord {
         } else {
             -outward_normal
         };
+        let (dpdu, dpdv) = Vec3::orthonormal_basis(&outward_normal);
         Self {
             p,
             normal,
+            geometric_normal: normal,
+            dpdu,
+            dpdv,
             t,
             front_face,
+            barycentric: (0.0, 0.0),
+            uv: (0.0, 0.0),
+            medium: None,
         }
     }
 
+    /// Set barycentric coordinates of the hit point on a triangle.
+    pub fn with_barycentric(mut self, u: f64, v: f64) -> Self {
+        self.barycentric = (u, v);
+        self
+    }
+
+    /// Set the surface (texture) coordinates of the hit point.
+    pub fn with_uv(mut self, u: f64, v: f64) -> Self {
+        self.uv = (u, v);
+        self
+    }
+
+    /// Set the partial derivatives of the surface position with respect to `u` and `v`.
+    /// Without them an arbitrary tangent frame around the normal is used.
+    pub fn with_derivatives(mut self, dpdu: Vec3, dpdv: Vec3) -> Self {
+        self.dpdu = dpdu;
+        self.dpdv = dpdv;
+        self
+    }
+
+    /// Marks the hit as entering a participating medium, which the ray leaves again at
+    /// `t_exit`.
+    pub fn with_medium(mut self, medium: Medium, t_exit: f64) -> Self {
+        self.medium = Some((medium, t_exit));
+        self
+    }
+
+    /// Moves the hit from object space into the space `transform` maps to. Normals keep facing
+    /// the transformed ray.
+    pub(crate) fn transformed(mut self, transform: &Transform) -> Self {
+        self.p = transform.point(&self.p);
+        self.normal = Vec3::unit_vector(&transform.normal(&self.normal));
+        self.geometric_normal = Vec3::unit_vector(&transform.normal(&self.geometric_normal));
+        self.dpdu = transform.vector(&self.dpdu);
+        self.dpdv = transform.vector(&self.dpdv);
+        self
+    }
+
+    /// Replace the normal used for shading (e.g. an interpolated vertex normal),
+    /// keeping it on the same side as the geometric normal.
+    pub fn set_shading_normal(&mut self, outward_normal: Vec3) {
+        self.normal = if self.front_face {
+            outward_normal
+        } else {
+            -outward_normal
+        };
+    }
+
     /// Get the hit record's p.
     pub fn p(&self) -> &Vec3 {
         &self.p
     }
 
-    /// Get the hit record's normal.
+    /// Get the hit record's shading normal, facing the incoming ray.
     pub fn normal(&self) -> &Vec3 {
         &self.normal
     }
 
+    /// Get the hit record's geometric normal, facing the incoming ray.
+    pub fn geometric_normal(&self) -> &Vec3 {
+        &self.geometric_normal
+    }
+
+    /// Get the hit record's position derivative along `u`.
+    pub fn dpdu(&self) -> &Vec3 {
+        &self.dpdu
+    }
+
+    /// Get the hit record's position derivative along `v`.
+    pub fn dpdv(&self) -> &Vec3 {
+        &self.dpdv
+    }
+
+    /// Orthonormal `(tangent, bitangent, normal)` frame around the shading normal, with the
+    /// tangent following `dpdu` as closely as possible.
+    pub fn tangent_frame(&self) -> (Vec3, Vec3, Vec3) {
+        let n = self.normal;
+        let tangent = self.dpdu - Vec3::dot(&self.dpdu, &n) * n;
+        if tangent.near_zero() {
+            let (t, b) = Vec3::orthonormal_basis(&n);
+            return (t, b, n);
+        }
+        let tangent = Vec3::unit_vector(&tangent);
+        (tangent, Vec3::cross(&n, &tangent), n)
+    }
+
     /// Get the hit record's t.
     pub fn t(&self) -> f64 {
         self.t
@@ -47,8 +142,25 @@ impl HitRecord {
     pub fn front_face(&self) -> bool {
         self.front_face
     }
+
+    /// Get the hit record's barycentric coordinates `(u, v)`, weights of the second and third
+    /// triangle vertices. Zero for non-triangle shapes.
+    pub fn barycentric(&self) -> (f64, f64) {
+        self.barycentric
+    }
+
+    /// Get the hit record's surface coordinates `(u, v)`, used for texture lookups.
+    pub fn uv(&self) -> (f64, f64) {
+        self.uv
+    }
+
+    /// Get the medium the ray entered and the `t` at which it leaves, `None` for surface hits.
+    pub fn medium(&self) -> Option<(&Medium, f64)> {
+        self.medium.as_ref().map(|(medium, t_exit)| (medium, *t_exit))
+    }
 }
 
 pub trait Hittable {
     fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+    fn bounding_box(&self) -> Aabb;
 }
@@ -0,0 +1,33 @@
+use crate::{ray::Ray, Transform};
+
+use super::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    shape::Shape,
+};
+
+/// Shape placed in the world by a transform from its own object space.
+pub struct Instance<'a> {
+    shape: &'a Shape,
+    transform: &'a Transform,
+}
+
+impl<'a> Instance<'a> {
+    pub fn new(shape: &'a Shape, transform: &'a Transform) -> Self {
+        Self { shape, transform }
+    }
+}
+
+impl Hittable for Instance<'_> {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // The object space direction is not normalized, so `t` is the same in both spaces
+        let object_ray = self.transform.inverse().ray(r);
+        self.shape
+            .hit(&object_ray, t_min, t_max)
+            .map(|rec| rec.transformed(self.transform))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.transform.aabb(&self.shape.bounding_box())
+    }
+}
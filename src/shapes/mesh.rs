@@ -0,0 +1,132 @@
+use crate::{bvh::Bvh, ray::Ray, Point3, Vec3};
+
+use super::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    triangle::Triangle,
+};
+
+/// Vertex indices of a single mesh triangle into the buffers of its [`Mesh`].
+#[derive(Debug, Clone, Copy)]
+pub struct MeshFace {
+    positions: [usize; 3],
+    normals: Option<[usize; 3]>,
+    uvs: Option<[usize; 3]>,
+}
+
+impl MeshFace {
+    pub fn new(
+        positions: [usize; 3],
+        normals: Option<[usize; 3]>,
+        uvs: Option<[usize; 3]>,
+    ) -> Self {
+        Self {
+            positions,
+            normals,
+            uvs,
+        }
+    }
+
+    /// Get the face's position indices.
+    pub fn positions(&self) -> [usize; 3] {
+        self.positions
+    }
+
+    /// Get the face's normal indices.
+    pub fn normals(&self) -> Option<[usize; 3]> {
+        self.normals
+    }
+
+    /// Get the face's texture coordinate indices.
+    pub fn uvs(&self) -> Option<[usize; 3]> {
+        self.uvs
+    }
+}
+
+/// Indexed triangle mesh. Faces share vertex data and are accelerated by their own BVH.
+pub struct Mesh {
+    positions: Vec<Point3>,
+    normals: Vec<Vec3>,
+    uvs: Vec<(f64, f64)>,
+    faces: Vec<MeshFace>,
+    bvh: Bvh,
+}
+
+impl Mesh {
+    /// Creates a mesh and builds its BVH.
+    ///
+    /// Panics if a face refers to a vertex, normal or texture coordinate that does not exist.
+    pub fn new(
+        positions: Vec<Point3>,
+        normals: Vec<Vec3>,
+        uvs: Vec<(f64, f64)>,
+        faces: Vec<MeshFace>,
+    ) -> Self {
+        for face in &faces {
+            assert!(face.positions.iter().all(|&i| i < positions.len()));
+            assert!(face.normals.iter().flatten().all(|&i| i < normals.len()));
+            assert!(face.uvs.iter().flatten().all(|&i| i < uvs.len()));
+        }
+
+        let mut mesh = Self {
+            positions,
+            normals,
+            uvs,
+            faces,
+            bvh: Bvh::default(),
+        };
+        let bounds: Vec<_> = (0..mesh.faces.len())
+            .map(|i| mesh.triangle(i).bounding_box())
+            .collect();
+        mesh.bvh = Bvh::new(&bounds);
+        mesh
+    }
+
+    fn triangle(&self, face: usize) -> Triangle {
+        let face = &self.faces[face];
+        let triangle = Triangle::new(
+            face.positions.map(|i| self.positions[i]),
+            face.normals.map(|n| n.map(|i| self.normals[i])),
+        );
+        match face.uvs {
+            Some(uvs) => triangle.with_uvs(uvs.map(|i| self.uvs[i])),
+            None => triangle,
+        }
+    }
+
+    /// Get a reference to the mesh's vertex positions.
+    pub fn positions(&self) -> &[Point3] {
+        &self.positions
+    }
+
+    /// Get a reference to the mesh's vertex normals.
+    pub fn normals(&self) -> &[Vec3] {
+        &self.normals
+    }
+
+    /// Get a reference to the mesh's texture coordinates.
+    pub fn uvs(&self) -> &[(f64, f64)] {
+        &self.uvs
+    }
+
+    /// Get a reference to the mesh's faces.
+    pub fn faces(&self) -> &[MeshFace] {
+        &self.faces
+    }
+}
+
+impl Hittable for Mesh {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.bvh
+            .hit(r, t_min, t_max, |face, r, t_min, t_max| {
+                self.triangle(face).hit(r, t_min, t_max)
+            })
+            .map(|(_, rec)| rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bvh
+            .bounds()
+            .unwrap_or_else(|| Aabb::new(Point3::default(), Point3::default()))
+    }
+}
@@ -1,7 +1,20 @@
+mod aabb;
+mod constant_medium;
+mod cuboid;
+mod grid_medium;
+mod mesh;
+mod moving_sphere;
+mod quad;
 mod shape;
 mod hittable;
+mod instance;
 mod sphere;
+mod triangle;
 
 pub type Shape = shape::Shape;
+pub type Aabb = aabb::Aabb;
+pub type Mesh = mesh::Mesh;
+pub type MeshFace = mesh::MeshFace;
 pub type HitRecord = hittable::HitRecord;
-pub use hittable::Hittable;
\ No newline at end of file
+pub use hittable::Hittable;
+pub(crate) use quad::Quad;
\ No newline at end of file
@@ -0,0 +1,50 @@
+use crate::{ray::Ray, Point3};
+
+use super::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+    sphere::Sphere,
+};
+
+/// Sphere moving in a straight line from `center0` at `time0` to `center1` at `time1`. It rests
+/// at the closer keyframe for times outside of that interval.
+pub struct MovingSphere {
+    center0: Point3,
+    center1: Point3,
+    time0: f64,
+    time1: f64,
+    radius: f64,
+}
+
+impl MovingSphere {
+    pub fn new(center0: Point3, center1: Point3, time0: f64, time1: f64, radius: f64) -> Self {
+        Self {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+        }
+    }
+
+    pub fn center(&self, time: f64) -> Point3 {
+        if self.time1 <= self.time0 {
+            return self.center0;
+        }
+        let s = ((time - self.time0) / (self.time1 - self.time0)).clamp(0.0, 1.0);
+        self.center0 + s * (self.center1 - self.center0)
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        Sphere::new(self.center(r.time()), self.radius).hit(r, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        // The motion is linear, so the boxes at both ends enclose the whole path
+        let start = Sphere::new(self.center0, self.radius).bounding_box();
+        let end = Sphere::new(self.center1, self.radius).bounding_box();
+        Aabb::surrounding(&start, &end)
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{ray::Ray, Point3, Vec3};
+
+use super::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+};
+
+/// Parallelogram spanned by the edges `u` and `v` from the corner `origin`. The front side is
+/// the one `u × v` points to.
+pub struct Quad {
+    origin: Point3,
+    u: Vec3,
+    v: Vec3,
+}
+
+impl Quad {
+    pub fn new(origin: Point3, u: Vec3, v: Vec3) -> Self {
+        Self { origin, u, v }
+    }
+
+    /// Get the quad's area.
+    pub fn area(&self) -> f64 {
+        Vec3::cross(&self.u, &self.v).len()
+    }
+
+    /// Get the quad's unit normal.
+    pub fn normal(&self) -> Vec3 {
+        Vec3::unit_vector(&Vec3::cross(&self.u, &self.v))
+    }
+
+    /// Point at the surface coordinates `(a, b)` in `[0, 1]`.
+    pub fn point(&self, a: f64, b: f64) -> Point3 {
+        self.origin + a * self.u + b * self.v
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let n = Vec3::cross(&self.u, &self.v);
+        let denominator = Vec3::dot(&n, r.dir());
+        if denominator.abs() < 1e-12 {
+            // The ray is parallel to the plane
+            return None;
+        }
+
+        let t = Vec3::dot(&n, &(self.origin - *r.orig())) / denominator;
+        if t < t_min || t_max < t {
+            return None;
+        }
+
+        // Express the hit point in the basis of the edges
+        let p = r.at(t);
+        let offset = p - self.origin;
+        let w = n / n.len_squared();
+        let a = Vec3::dot(&w, &Vec3::cross(&offset, &self.v));
+        let b = Vec3::dot(&w, &Vec3::cross(&self.u, &offset));
+        if !(0.0..=1.0).contains(&a) || !(0.0..=1.0).contains(&b) {
+            return None;
+        }
+
+        Some(
+            HitRecord::new(p, Vec3::unit_vector(&n), t, r)
+                .with_uv(a, b)
+                .with_derivatives(self.u, self.v),
+        )
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let mut bbox = Aabb::from_points(&self.origin, &(self.origin + self.u + self.v));
+        bbox.expand(&(self.origin + self.u));
+        bbox.expand(&(self.origin + self.v));
+        // Axis-aligned quads have no thickness along one of the axes
+        bbox.pad(1e-4)
+    }
+}
@@ -1,9 +1,79 @@
-use crate::{Point3, ray::Ray};
+use std::sync::Arc;
 
-use super::{hittable::{Hittable, HitRecord}, sphere::Sphere};
+use crate::{ray::Ray, Point3, Transform, Vec3, VoxelGrid};
+
+use super::{
+    aabb::Aabb,
+    constant_medium::ConstantMedium,
+    cuboid::Cuboid,
+    grid_medium::GridMedium,
+    hittable::{HitRecord, Hittable},
+    instance::Instance,
+    mesh::Mesh,
+    moving_sphere::MovingSphere,
+    quad::Quad,
+    sphere::Sphere,
+    triangle::Triangle,
+};
 
 pub enum Shape {
     Sphere { center: Point3, radius: f64 },
+    /// Sphere moving from `center0` at `time0` to `center1` at `time1`, for motion blur.
+    MovingSphere { center0: Point3, center1: Point3, time0: f64, time1: f64, radius: f64 },
+    Triangle { vertices: [Point3; 3], normals: Option<[Vec3; 3]> },
+    Mesh { mesh: Arc<Mesh> },
+    /// Parallelogram with corner `origin` and edges `u` and `v`; it faces towards `u × v`.
+    Quad { origin: Point3, u: Vec3, v: Vec3 },
+    /// Axis-aligned box between the corners `min` and `max`.
+    Box { min: Point3, max: Point3 },
+    /// Another shape moved into place by `transform`. The shape can be shared by any number of
+    /// instances.
+    Instance { shape: Arc<Shape>, transform: Arc<Transform> },
+    /// Fog or smoke of uniform `density` filling the convex `boundary`. Pair it with
+    /// [`crate::Material::Isotropic`].
+    ConstantMedium { boundary: Arc<Shape>, density: f64 },
+    /// Smoke whose density is the `grid` stretched over the box between `min` and `max`,
+    /// scaled by `density`. Flat boxes hold no smoke and are never hit.
+    GridMedium { grid: Arc<VoxelGrid>, min: Point3, max: Point3, density: f64 },
+}
+
+impl Shape {
+    /// Rectangle in the plane `z = k`, facing +Z.
+    pub fn xy_rect(x0: f64, x1: f64, y0: f64, y1: f64, k: f64) -> Self {
+        Shape::Quad {
+            origin: Point3::new(x0, y0, k),
+            u: Vec3::new(x1 - x0, 0.0, 0.0),
+            v: Vec3::new(0.0, y1 - y0, 0.0),
+        }
+    }
+
+    /// Rectangle in the plane `y = k`, facing +Y.
+    pub fn xz_rect(x0: f64, x1: f64, z0: f64, z1: f64, k: f64) -> Self {
+        Shape::Quad {
+            origin: Point3::new(x0, k, z0),
+            u: Vec3::new(0.0, 0.0, z1 - z0),
+            v: Vec3::new(x1 - x0, 0.0, 0.0),
+        }
+    }
+
+    /// Rectangle in the plane `x = k`, facing +X.
+    pub fn yz_rect(y0: f64, y1: f64, z0: f64, z1: f64, k: f64) -> Self {
+        Shape::Quad {
+            origin: Point3::new(k, y0, z0),
+            u: Vec3::new(0.0, y1 - y0, 0.0),
+            v: Vec3::new(0.0, 0.0, z1 - z0),
+        }
+    }
+
+    /// Voxel grid behind the `Grid` media recorded in this shape's hits, looking through
+    /// instances.
+    pub(crate) fn voxel_grid(&self) -> Option<&VoxelGrid> {
+        match self {
+            Shape::GridMedium { grid, .. } => Some(grid),
+            Shape::Instance { shape, .. } => shape.voxel_grid(),
+            _ => None,
+        }
+    }
 }
 
 impl Hittable for Shape {
@@ -13,6 +83,73 @@ impl Hittable for Shape {
                 let shape = Sphere::new(*center, *radius);
                 shape.hit(r, t_min, t_max)
             },
+            Shape::MovingSphere { center0, center1, time0, time1, radius } => {
+                let shape = MovingSphere::new(*center0, *center1, *time0, *time1, *radius);
+                shape.hit(r, t_min, t_max)
+            },
+            Shape::Triangle { vertices, normals } => {
+                let shape = Triangle::new(*vertices, *normals);
+                shape.hit(r, t_min, t_max)
+            },
+            Shape::Mesh { mesh } => mesh.hit(r, t_min, t_max),
+            Shape::Quad { origin, u, v } => {
+                let shape = Quad::new(*origin, *u, *v);
+                shape.hit(r, t_min, t_max)
+            },
+            Shape::Box { min, max } => {
+                let shape = Cuboid::new(*min, *max);
+                shape.hit(r, t_min, t_max)
+            },
+            Shape::Instance { shape, transform } => {
+                let shape = Instance::new(shape, transform);
+                shape.hit(r, t_min, t_max)
+            },
+            Shape::ConstantMedium { boundary, density } => {
+                let shape = ConstantMedium::new(boundary, *density);
+                shape.hit(r, t_min, t_max)
+            },
+            Shape::GridMedium { min, max, density, .. } => {
+                let shape = GridMedium::new(*min, *max, *density);
+                shape.hit(r, t_min, t_max)
+            },
+        }
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        match self {
+            Shape::Sphere { center, radius } => {
+                let shape = Sphere::new(*center, *radius);
+                shape.bounding_box()
+            },
+            Shape::MovingSphere { center0, center1, time0, time1, radius } => {
+                let shape = MovingSphere::new(*center0, *center1, *time0, *time1, *radius);
+                shape.bounding_box()
+            },
+            Shape::Triangle { vertices, normals } => {
+                let shape = Triangle::new(*vertices, *normals);
+                shape.bounding_box()
+            },
+            Shape::Mesh { mesh } => mesh.bounding_box(),
+            Shape::Quad { origin, u, v } => {
+                let shape = Quad::new(*origin, *u, *v);
+                shape.bounding_box()
+            },
+            Shape::Box { min, max } => {
+                let shape = Cuboid::new(*min, *max);
+                shape.bounding_box()
+            },
+            Shape::Instance { shape, transform } => {
+                let shape = Instance::new(shape, transform);
+                shape.bounding_box()
+            },
+            Shape::ConstantMedium { boundary, density } => {
+                let shape = ConstantMedium::new(boundary, *density);
+                shape.bounding_box()
+            },
+            Shape::GridMedium { min, max, density, .. } => {
+                let shape = GridMedium::new(*min, *max, *density);
+                shape.bounding_box()
+            },
         }
     }
 }
\ No newline at end of file
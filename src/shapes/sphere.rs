@@ -1,6 +1,11 @@
+use std::f64::consts::PI;
+
 use crate::{ray::Ray, Point3, Vec3};
 
-use super::hittable::{HitRecord, Hittable};
+use super::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+};
 
 pub struct Sphere {
     center: Point3,
@@ -11,6 +16,27 @@ impl Sphere {
     pub fn new(center: Point3, radius: f64) -> Self {
         Self { center, radius }
     }
+
+    /// Surface coordinates of a point on the unit sphere: `u` goes around the Y axis starting
+    /// at -X, `v` goes from the bottom (-Y) to the top pole.
+    fn uv(p: &Point3) -> (f64, f64) {
+        let theta = (-p.y()).clamp(-1.0, 1.0).acos();
+        let phi = (-p.z()).atan2(p.x()) + PI;
+        (phi / (2.0 * PI), theta / PI)
+    }
+
+    /// Derivatives of the position with respect to the coordinates of [`Sphere::uv`], for a
+    /// point `p` relative to the center.
+    fn derivatives(&self, p: &Vec3) -> (Vec3, Vec3) {
+        let dpdu = 2.0 * PI * Vec3::new(p.z(), 0.0, -p.x());
+        let rho = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        if rho < 1e-12 * self.radius.abs() {
+            // At the poles u is undefined, any frame around the normal will do
+            return Vec3::orthonormal_basis(&(*p / self.radius));
+        }
+        let dpdv = PI * Vec3::new(-p.y() * p.x() / rho, rho, -p.y() * p.z() / rho);
+        (dpdu, dpdv)
+    }
 }
 
 impl Hittable for Sphere {
@@ -39,6 +65,37 @@ impl Hittable for Sphere {
         let p = r.at(root);
         let normal = (p - self.center) / self.radius;
 
-        Some(HitRecord::new(p, normal, root, r))
+        // Hollow spheres flip the normal, but not their texture
+        let (u, v) = Self::uv(&((p - self.center) / self.radius.abs()));
+        let (dpdu, dpdv) = self.derivatives(&(p - self.center));
+
+        Some(
+            HitRecord::new(p, normal, root, r)
+                .with_uv(u, v)
+                .with_derivatives(dpdu, dpdv),
+        )
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let r = Vec3::new(self.radius.abs(), self.radius.abs(), self.radius.abs());
+        Aabb::new(self.center - r, self.center + r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hollow_sphere_keeps_uv() {
+        let center = Point3::new(1.0, 2.0, 3.0);
+        let r = Ray::new(Point3::new(1.3, 2.4, 10.0), Vec3::new(0.0, 0.0, -1.0));
+        let solid = Sphere::new(center, 1.0);
+        let hollow = Sphere::new(center, -1.0);
+        let solid = solid.hit(&r, 0.001, f64::INFINITY).unwrap();
+        let hollow = hollow.hit(&r, 0.001, f64::INFINITY).unwrap();
+        assert!((solid.t() - hollow.t()).abs() < 1e-12);
+        let ((su, sv), (hu, hv)) = (solid.uv(), hollow.uv());
+        assert!((su - hu).abs() < 1e-12 && (sv - hv).abs() < 1e-12);
     }
 }
@@ -0,0 +1,112 @@
+use crate::{ray::Ray, Point3, Vec3};
+
+use super::{
+    aabb::Aabb,
+    hittable::{HitRecord, Hittable},
+};
+
+pub struct Triangle {
+    vertices: [Point3; 3],
+    normals: Option<[Vec3; 3]>,
+    uvs: Option<[(f64, f64); 3]>,
+}
+
+impl Triangle {
+    pub fn new(vertices: [Point3; 3], normals: Option<[Vec3; 3]>) -> Self {
+        Self {
+            vertices,
+            normals,
+            uvs: None,
+        }
+    }
+
+    /// Set per-vertex texture coordinates. Without them the barycentric coordinates are used.
+    pub fn with_uvs(mut self, uvs: [(f64, f64); 3]) -> Self {
+        self.uvs = Some(uvs);
+        self
+    }
+
+    /// Solves for the position derivatives along the texture coordinates, or `None` if the
+    /// texture coordinates are degenerate.
+    fn derivatives(vertices: &[Point3; 3], uvs: &[(f64, f64); 3]) -> Option<(Vec3, Vec3)> {
+        let [p0, p1, p2] = *vertices;
+        let [uv0, uv1, uv2] = *uvs;
+        let (du02, dv02) = (uv0.0 - uv2.0, uv0.1 - uv2.1);
+        let (du12, dv12) = (uv1.0 - uv2.0, uv1.1 - uv2.1);
+        let (dp02, dp12) = (p0 - p2, p1 - p2);
+
+        let determinant = du02 * dv12 - dv02 * du12;
+        if determinant.abs() < 1e-12 {
+            return None;
+        }
+        let inv_det = 1.0 / determinant;
+        let dpdu = inv_det * (dv12 * dp02 - dv02 * dp12);
+        let dpdv = inv_det * (du02 * dp12 - du12 * dp02);
+        Some((dpdu, dpdv))
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, r: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // Möller–Trumbore intersection
+        let [v0, v1, v2] = self.vertices;
+        let edge1 = v1 - v0;
+        let edge2 = v2 - v0;
+
+        let pvec = Vec3::cross(r.dir(), &edge2);
+        let det = Vec3::dot(&edge1, &pvec);
+        if det.abs() < 1e-12 {
+            // The ray is parallel to the triangle's plane
+            return None;
+        }
+        let inv_det = 1.0 / det;
+
+        let tvec = *r.orig() - v0;
+        let u = Vec3::dot(&tvec, &pvec) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let qvec = Vec3::cross(&tvec, &edge1);
+        let v = Vec3::dot(r.dir(), &qvec) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = Vec3::dot(&edge2, &qvec) * inv_det;
+        if t < t_min || t_max < t {
+            return None;
+        }
+
+        let normal = Vec3::unit_vector(&Vec3::cross(&edge1, &edge2));
+        let uvs = self.uvs.unwrap_or([(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]);
+        let [uv0, uv1, uv2] = uvs;
+        let uv = (
+            (1.0 - u - v) * uv0.0 + u * uv1.0 + v * uv2.0,
+            (1.0 - u - v) * uv0.1 + u * uv1.1 + v * uv2.1,
+        );
+        let mut rec = HitRecord::new(r.at(t), normal, t, r)
+            .with_barycentric(u, v)
+            .with_uv(uv.0, uv.1);
+        if let Some((dpdu, dpdv)) = Self::derivatives(&self.vertices, &uvs) {
+            rec = rec.with_derivatives(dpdu, dpdv);
+        }
+
+        if let Some([n0, n1, n2]) = self.normals {
+            let shading_normal = (1.0 - u - v) * n0 + u * n1 + v * n2;
+            if !shading_normal.near_zero() {
+                rec.set_shading_normal(Vec3::unit_vector(&shading_normal));
+            }
+        }
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let [v0, v1, v2] = self.vertices;
+        let mut bbox = Aabb::from_points(&v0, &v1);
+        bbox.expand(&v2);
+        // Axis-aligned triangles have no thickness along one of the axes
+        bbox.pad(1e-4)
+    }
+}
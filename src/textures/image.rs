@@ -0,0 +1,62 @@
+use std::{io, path::Path};
+
+use crate::{loaders::image::load_image, Color, Point3};
+
+use super::texture::TextureValue;
+
+/// Bitmap with linear colors, mapped onto surfaces by their UV coordinates.
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl ImageTexture {
+    /// Creates a texture from pixels stored row by row, starting at the top (v = 1) row.
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert_eq!(width * height, pixels.len());
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Loads a PNG, JPEG or Radiance HDR image.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let (width, height, pixels) = load_image(path)?;
+        Ok(Self::new(width, height, pixels))
+    }
+
+    /// Get the texture's width.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Get the texture's height.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+}
+
+impl TextureValue for ImageTexture {
+    /// Bilinearly filtered color at `uv`, repeating the image outside of `[0, 1]`.
+    fn value(&self, uv: (f64, f64), _p: &Point3) -> Color {
+        if self.pixels.is_empty() {
+            return Color::default();
+        }
+
+        // Pixel centers are at half-integer coordinates, and v points up
+        let x = uv.0 * self.width as f64 - 0.5;
+        let y = (1.0 - uv.1) * self.height as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+
+        let wrap = |value: f64, size: usize| (value as i64).rem_euclid(size as i64) as usize;
+        let texel =
+            |x: f64, y: f64| self.pixels[wrap(y, self.height) * self.width + wrap(x, self.width)];
+
+        (1.0 - fy) * ((1.0 - fx) * texel(x0, y0) + fx * texel(x0 + 1.0, y0))
+            + fy * ((1.0 - fx) * texel(x0, y0 + 1.0) + fx * texel(x0 + 1.0, y0 + 1.0))
+    }
+}
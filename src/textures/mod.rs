@@ -0,0 +1,8 @@
+mod image;
+mod perlin;
+mod texture;
+
+pub type Texture = texture::Texture;
+pub type ImageTexture = image::ImageTexture;
+pub type Perlin = perlin::Perlin;
+pub use texture::TextureValue;
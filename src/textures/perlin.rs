@@ -0,0 +1,111 @@
+use std::sync::OnceLock;
+
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_pcg::Pcg32;
+
+use crate::{Point3, Vec3};
+
+const POINT_COUNT: usize = 256;
+
+/// Gradient noise over lattices of random unit vectors (Perlin, "An Image Synthesizer").
+pub struct Perlin {
+    gradients: Vec<Vec3>,
+    perm_x: Vec<usize>,
+    perm_y: Vec<usize>,
+    perm_z: Vec<usize>,
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Pcg32::seed_from_u64(seed);
+        let gradients = (0..POINT_COUNT)
+            .map(|_| Vec3::unit_vector(&Vec3::random(-1.0, 1.0, &mut rng)))
+            .collect();
+        let mut permutation = || {
+            let mut p: Vec<usize> = (0..POINT_COUNT).collect();
+            p.shuffle(&mut rng);
+            p
+        };
+        let perm_x = permutation();
+        let perm_y = permutation();
+        let perm_z = permutation();
+
+        Self {
+            gradients,
+            perm_x,
+            perm_y,
+            perm_z,
+        }
+    }
+
+    /// Shared generator, so that noise textures look the same in every render.
+    pub fn shared() -> &'static Perlin {
+        static PERLIN: OnceLock<Perlin> = OnceLock::new();
+        PERLIN.get_or_init(|| Perlin::new(0))
+    }
+
+    /// Smooth noise in `[-1, 1]`.
+    pub fn noise(&self, p: &Point3) -> f64 {
+        let (u, v, w) = (
+            p.x() - p.x().floor(),
+            p.y() - p.y().floor(),
+            p.z() - p.z().floor(),
+        );
+        let (i, j, k) = (
+            p.x().floor() as i64,
+            p.y().floor() as i64,
+            p.z().floor() as i64,
+        );
+
+        let mut corners = [[[Vec3::default(); 2]; 2]; 2];
+        for (di, plane) in corners.iter_mut().enumerate() {
+            for (dj, row) in plane.iter_mut().enumerate() {
+                for (dk, corner) in row.iter_mut().enumerate() {
+                    let index = self.perm_x[((i + di as i64) & 255) as usize]
+                        ^ self.perm_y[((j + dj as i64) & 255) as usize]
+                        ^ self.perm_z[((k + dk as i64) & 255) as usize];
+                    *corner = self.gradients[index];
+                }
+            }
+        }
+
+        Self::interpolate(&corners, u, v, w)
+    }
+
+    /// Sum of `depth` octaves of noise with halving amplitude, in `[0, 2)`.
+    pub fn turbulence(&self, p: &Point3, depth: u32) -> f64 {
+        let mut accum = 0.0;
+        let mut p = *p;
+        let mut weight = 1.0;
+        for _ in 0..depth {
+            accum += weight * self.noise(&p);
+            weight *= 0.5;
+            p = 2.0 * p;
+        }
+        accum.abs()
+    }
+
+    fn interpolate(corners: &[[[Vec3; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f64 {
+        // Hermite smoothing hides the lattice
+        let (uu, vv, ww) = (
+            u * u * (3.0 - 2.0 * u),
+            v * v * (3.0 - 2.0 * v),
+            w * w * (3.0 - 2.0 * w),
+        );
+
+        let mut accum = 0.0;
+        for (i, plane) in corners.iter().enumerate() {
+            for (j, row) in plane.iter().enumerate() {
+                for (k, gradient) in row.iter().enumerate() {
+                    let (fi, fj, fk) = (i as f64, j as f64, k as f64);
+                    let weight = Vec3::new(u - fi, v - fj, w - fk);
+                    accum += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                        * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                        * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                        * Vec3::dot(gradient, &weight);
+                }
+            }
+        }
+        accum
+    }
+}
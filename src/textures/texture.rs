@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use crate::{Color, Point3};
+
+use super::{image::ImageTexture, perlin::Perlin};
+
+pub trait TextureValue: Sync + Send {
+    /// Color of the surface at texture coordinates `uv` and position `p`.
+    fn value(&self, uv: (f64, f64), p: &Point3) -> Color;
+}
+
+/// Spatially varying color, looked up by materials at every hit point.
+#[derive(Clone)]
+pub enum Texture {
+    Solid {
+        color: Color,
+    },
+    /// Alternating cubes of size `scale` filling space, so that the pattern does not depend on
+    /// the surface parameterization.
+    Checker {
+        scale: f64,
+        even: Color,
+        odd: Color,
+    },
+    Image {
+        image: Arc<ImageTexture>,
+    },
+    /// Perlin noise with features of size `scale`.
+    Noise {
+        scale: f64,
+    },
+    /// Sum of `octaves` layers of Perlin noise.
+    Turbulence {
+        scale: f64,
+        octaves: u32,
+    },
+    /// Sine stripes along the z axis, with the phase perturbed by turbulence.
+    Marble {
+        scale: f64,
+        octaves: u32,
+    },
+}
+
+impl From<Color> for Texture {
+    fn from(color: Color) -> Self {
+        Texture::Solid { color }
+    }
+}
+
+impl TextureValue for Texture {
+    fn value(&self, uv: (f64, f64), p: &Point3) -> Color {
+        match self {
+            Texture::Solid { color } => *color,
+            Texture::Checker { scale, even, odd } => {
+                let cell = |x: f64| (x / scale).floor() as i64;
+                if (cell(p.x()) + cell(p.y()) + cell(p.z())).rem_euclid(2) == 0 {
+                    *even
+                } else {
+                    *odd
+                }
+            }
+            Texture::Image { image } => image.value(uv, p),
+            Texture::Noise { scale } => {
+                let noise = Perlin::shared().noise(&(*p / *scale));
+                0.5 * (1.0 + noise) * Color::new(1.0, 1.0, 1.0)
+            }
+            Texture::Turbulence { scale, octaves } => {
+                let turbulence = Perlin::shared().turbulence(&(*p / *scale), *octaves);
+                turbulence.min(1.0) * Color::new(1.0, 1.0, 1.0)
+            }
+            Texture::Marble { scale, octaves } => {
+                let q = *p / *scale;
+                let turbulence = Perlin::shared().turbulence(&q, *octaves);
+                0.5 * (1.0 + (q.z() + 10.0 * turbulence).sin()) * Color::new(1.0, 1.0, 1.0)
+            }
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use std::ops::Mul;
+
+use crate::{ray::Ray, shapes::Aabb, Point3, Vec3};
+
+type Matrix = [[f64; 4]; 4];
+
+const IDENTITY: Matrix = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// Affine transform stored as a 4x4 matrix together with its inverse.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    matrix: Matrix,
+    inverse: Matrix,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self {
+            matrix: IDENTITY,
+            inverse: IDENTITY,
+        }
+    }
+
+    /// Creates a transform from a row-major matrix, or `None` if it can't be inverted.
+    pub fn from_matrix(matrix: [[f64; 4]; 4]) -> Option<Self> {
+        let inverse = invert(&matrix)?;
+        Some(Self { matrix, inverse })
+    }
+
+    pub fn translation(offset: Vec3) -> Self {
+        let mut matrix = IDENTITY;
+        let mut inverse = IDENTITY;
+        for axis in 0..3 {
+            matrix[axis][3] = offset[axis];
+            inverse[axis][3] = -offset[axis];
+        }
+        Self { matrix, inverse }
+    }
+
+    /// Scales by a factor per axis. Panics if a factor is zero.
+    pub fn scaling(factors: Vec3) -> Self {
+        assert!(
+            factors.x() != 0.0 && factors.y() != 0.0 && factors.z() != 0.0,
+            "scale factors must not be zero"
+        );
+        let mut matrix = IDENTITY;
+        let mut inverse = IDENTITY;
+        for axis in 0..3 {
+            matrix[axis][axis] = factors[axis];
+            inverse[axis][axis] = 1.0 / factors[axis];
+        }
+        Self { matrix, inverse }
+    }
+
+    /// Rotates counterclockwise by `degrees` around `axis`, looking against the axis.
+    pub fn rotation(axis: Vec3, degrees: f64) -> Self {
+        let a = Vec3::unit_vector(&axis);
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let matrix = [
+            [
+                a.x() * a.x() + (1.0 - a.x() * a.x()) * cos,
+                a.x() * a.y() * (1.0 - cos) - a.z() * sin,
+                a.x() * a.z() * (1.0 - cos) + a.y() * sin,
+                0.0,
+            ],
+            [
+                a.x() * a.y() * (1.0 - cos) + a.z() * sin,
+                a.y() * a.y() + (1.0 - a.y() * a.y()) * cos,
+                a.y() * a.z() * (1.0 - cos) - a.x() * sin,
+                0.0,
+            ],
+            [
+                a.x() * a.z() * (1.0 - cos) - a.y() * sin,
+                a.y() * a.z() * (1.0 - cos) + a.x() * sin,
+                a.z() * a.z() + (1.0 - a.z() * a.z()) * cos,
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ];
+        // Rotations are orthogonal
+        Self {
+            matrix,
+            inverse: transpose(&matrix),
+        }
+    }
+
+    pub fn rotation_x(degrees: f64) -> Self {
+        Self::rotation(Vec3::new(1.0, 0.0, 0.0), degrees)
+    }
+
+    pub fn rotation_y(degrees: f64) -> Self {
+        Self::rotation(Vec3::new(0.0, 1.0, 0.0), degrees)
+    }
+
+    pub fn rotation_z(degrees: f64) -> Self {
+        Self::rotation(Vec3::new(0.0, 0.0, 1.0), degrees)
+    }
+
+    /// Applies `self` first and `next` afterwards.
+    pub fn then(&self, next: &Transform) -> Self {
+        *next * *self
+    }
+
+    /// Factor by which the transform scales every length, or `None` if it stretches some
+    /// directions more than others and so turns spheres into ellipsoids.
+    pub fn uniform_scale(&self) -> Option<f64> {
+        let axes = [
+            self.vector(&Vec3::new(1.0, 0.0, 0.0)),
+            self.vector(&Vec3::new(0.0, 1.0, 0.0)),
+            self.vector(&Vec3::new(0.0, 0.0, 1.0)),
+        ];
+        let scale = axes[0].len();
+        let tolerance = 1e-9 * scale;
+        let uniform = (0..3).all(|a| {
+            (axes[a].len() - scale).abs() <= tolerance
+                && Vec3::dot(&axes[a], &axes[(a + 1) % 3]).abs() <= tolerance * scale
+        });
+        uniform.then_some(scale)
+    }
+
+    pub fn inverse(&self) -> Self {
+        Self {
+            matrix: self.inverse,
+            inverse: self.matrix,
+        }
+    }
+
+    /// Get the transform's row-major matrix.
+    pub fn matrix(&self) -> &[[f64; 4]; 4] {
+        &self.matrix
+    }
+
+    /// Get the transform's row-major inverse matrix.
+    pub fn inverse_matrix(&self) -> &[[f64; 4]; 4] {
+        &self.inverse
+    }
+
+    pub fn point(&self, p: &Point3) -> Point3 {
+        let m = &self.matrix;
+        let x = m[0][0] * p.x() + m[0][1] * p.y() + m[0][2] * p.z() + m[0][3];
+        let y = m[1][0] * p.x() + m[1][1] * p.y() + m[1][2] * p.z() + m[1][3];
+        let z = m[2][0] * p.x() + m[2][1] * p.y() + m[2][2] * p.z() + m[2][3];
+        let w = m[3][0] * p.x() + m[3][1] * p.y() + m[3][2] * p.z() + m[3][3];
+        if w == 1.0 {
+            Point3::new(x, y, z)
+        } else {
+            Point3::new(x, y, z) / w
+        }
+    }
+
+    /// Transforms a direction, ignoring the translation.
+    pub fn vector(&self, v: &Vec3) -> Vec3 {
+        let m = &self.matrix;
+        Vec3::new(
+            m[0][0] * v.x() + m[0][1] * v.y() + m[0][2] * v.z(),
+            m[1][0] * v.x() + m[1][1] * v.y() + m[1][2] * v.z(),
+            m[2][0] * v.x() + m[2][1] * v.y() + m[2][2] * v.z(),
+        )
+    }
+
+    /// Transforms a surface normal with the inverse transpose, so that it stays perpendicular
+    /// to the transformed surface. The result is not normalized.
+    pub fn normal(&self, n: &Vec3) -> Vec3 {
+        let m = &self.inverse;
+        Vec3::new(
+            m[0][0] * n.x() + m[1][0] * n.y() + m[2][0] * n.z(),
+            m[0][1] * n.x() + m[1][1] * n.y() + m[2][1] * n.z(),
+            m[0][2] * n.x() + m[1][2] * n.y() + m[2][2] * n.z(),
+        )
+    }
+
+    /// Transforms origin and direction. The direction is not normalized, so distances along
+    /// the ray stay the same.
+    pub fn ray(&self, r: &Ray) -> Ray {
+        Ray::new(self.point(r.orig()), self.vector(r.dir())).with_time(r.time())
+    }
+
+    /// Box enclosing the transformed corners of `bbox`.
+    pub fn aabb(&self, bbox: &Aabb) -> Aabb {
+        let (min, max) = (bbox.min(), bbox.max());
+        let corner = |i: usize| {
+            Point3::new(
+                if i & 1 == 0 { min.x() } else { max.x() },
+                if i & 2 == 0 { min.y() } else { max.y() },
+                if i & 4 == 0 { min.z() } else { max.z() },
+            )
+        };
+        let first = self.point(&corner(0));
+        let mut result = Aabb::new(first, first);
+        for i in 1..8 {
+            result.expand(&self.point(&corner(i)));
+        }
+        result
+    }
+}
+
+/// Matrix product: the right hand side gets applied first.
+impl Mul for Transform {
+    type Output = Transform;
+
+    fn mul(self, rhs: Transform) -> Transform {
+        Transform {
+            matrix: multiply(&self.matrix, &rhs.matrix),
+            inverse: multiply(&rhs.inverse, &self.inverse),
+        }
+    }
+}
+
+fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut result = [[0.0; 4]; 4];
+    for (i, row) in result.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = (0..4).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    result
+}
+
+fn transpose(m: &Matrix) -> Matrix {
+    let mut result = [[0.0; 4]; 4];
+    for (i, row) in result.iter_mut().enumerate() {
+        for (j, value) in row.iter_mut().enumerate() {
+            *value = m[j][i];
+        }
+    }
+    result
+}
+
+/// Gauss-Jordan elimination with partial pivoting.
+fn invert(m: &Matrix) -> Option<Matrix> {
+    let mut a = *m;
+    let mut inverse = IDENTITY;
+
+    for column in 0..4 {
+        let pivot =
+            (column..4).max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))?;
+        if a[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(column, pivot);
+        inverse.swap(column, pivot);
+
+        let scale = 1.0 / a[column][column];
+        for j in 0..4 {
+            a[column][j] *= scale;
+            inverse[column][j] *= scale;
+        }
+
+        for row in 0..4 {
+            if row == column {
+                continue;
+            }
+            let factor = a[row][column];
+            for j in 0..4 {
+                a[row][j] -= factor * a[column][j];
+                inverse[row][j] -= factor * inverse[column][j];
+            }
+        }
+    }
+
+    Some(inverse)
+}
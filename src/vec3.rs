@@ -1,3 +1,4 @@
+use std::f64::consts::PI;
 use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub};
 
 use rand::Rng;
@@ -8,12 +9,11 @@ pub struct Vec3 {
 }
 
 impl Vec3 {
-    pub fn new(e0: f64, e1: f64, e2: f64) -> Self {
+    pub const fn new(e0: f64, e1: f64, e2: f64) -> Self {
         Self { e: [e0, e1, e2] }
     }
 
-    pub fn random(min: f64, max: f64) -> Self {
-        let mut rng = rand::thread_rng();
+    pub fn random<R: Rng + ?Sized>(min: f64, max: f64, rng: &mut R) -> Self {
         let range = min..max;
         Self {
             e: [
@@ -24,13 +24,13 @@ impl Vec3 {
         }
     }
 
-    pub fn random_vec() -> Vec3 {
-        Vec3::random(0.0, 1.0)
+    pub fn random_vec<R: Rng + ?Sized>(rng: &mut R) -> Vec3 {
+        Vec3::random(0.0, 1.0, rng)
     }
 
-    pub fn random_in_unit_sphere() -> Self {
+    pub fn random_in_unit_sphere<R: Rng + ?Sized>(rng: &mut R) -> Self {
         loop {
-            let p = Self::random(-1.0, 1.0);
+            let p = Self::random(-1.0, 1.0, rng);
             if p.len_squared() >= 1.0 {
                 continue;
             }
@@ -71,15 +71,15 @@ impl Vec3 {
     }
 
     pub fn unit_vector(v: &Vec3) -> Vec3 {
-        *v / (v.len() as f64)
+        *v / v.len()
     }
 
-    pub fn random_unit_vector() -> Vec3 {
-        Vec3::unit_vector(&Vec3::random_in_unit_sphere())
+    pub fn random_unit_vector<R: Rng + ?Sized>(rng: &mut R) -> Vec3 {
+        Vec3::unit_vector(&Vec3::random_in_unit_sphere(rng))
     }
 
-    pub fn random_in_hemisphere(normal: &Vec3) -> Vec3 {
-        let in_unit_sphere = Vec3::random_in_unit_sphere();
+    pub fn random_in_hemisphere<R: Rng + ?Sized>(normal: &Vec3, rng: &mut R) -> Vec3 {
+        let in_unit_sphere = Vec3::random_in_unit_sphere(rng);
         if Vec3::dot(&in_unit_sphere, normal) > 0.0 {
             in_unit_sphere
         } else {
@@ -87,6 +87,11 @@ impl Vec3 {
         }
     }
 
+    /// Relative luminance of a linear Rec. 709 color.
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.e[0] + 0.7152 * self.e[1] + 0.0722 * self.e[2]
+    }
+
     pub fn near_zero(&self) -> bool {
         let s = 1e-8;
         self.e[0].abs() < s && self.e[1].abs() < s && self.e[2].abs() < s
@@ -103,8 +108,49 @@ impl Vec3 {
         r_out_perp + r_out_parallel
     }
 
-    pub fn random_in_unit_disk() -> Vec3 {
-        let mut rng = rand::thread_rng();
+    /// Maps a point of the unit square uniformly onto the surface of the unit sphere.
+    pub fn sample_unit_sphere(u1: f64, u2: f64) -> Vec3 {
+        let z = 1.0 - 2.0 * u1;
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        let phi = 2.0 * PI * u2;
+        Vec3::new(r * phi.cos(), r * phi.sin(), z)
+    }
+
+    /// Maps a point of the unit cube uniformly into the unit ball.
+    pub fn sample_in_unit_sphere(u1: f64, u2: f64, u3: f64) -> Vec3 {
+        u3.cbrt() * Vec3::sample_unit_sphere(u1, u2)
+    }
+
+    /// Maps a point of the unit square uniformly onto the unit disk in the XY plane,
+    /// using Shirley's concentric mapping to keep stratification intact.
+    pub fn sample_in_unit_disk(u1: f64, u2: f64) -> Vec3 {
+        let a = 2.0 * u1 - 1.0;
+        let b = 2.0 * u2 - 1.0;
+        if a == 0.0 && b == 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+
+        let (r, theta) = if a.abs() > b.abs() {
+            (a, PI / 4.0 * (b / a))
+        } else {
+            (b, PI / 2.0 - PI / 4.0 * (a / b))
+        };
+        Vec3::new(r * theta.cos(), r * theta.sin(), 0.0)
+    }
+
+    /// Two unit vectors that together with the unit vector `n` form an orthonormal basis.
+    pub fn orthonormal_basis(n: &Vec3) -> (Vec3, Vec3) {
+        // Duff et al., "Building an Orthonormal Basis, Revisited"
+        let sign = 1f64.copysign(n.z());
+        let a = -1.0 / (sign + n.z());
+        let b = n.x() * n.y() * a;
+        (
+            Vec3::new(1.0 + sign * n.x() * n.x() * a, sign * b, -sign * n.x()),
+            Vec3::new(b, sign + n.y() * n.y() * a, -n.y()),
+        )
+    }
+
+    pub fn random_in_unit_disk<R: Rng + ?Sized>(rng: &mut R) -> Vec3 {
         let range = -1.0..1.0;
 
         loop {
@@ -0,0 +1,166 @@
+use std::{
+    io::{self, ErrorKind},
+    path::Path,
+};
+
+use crate::{loaders::voxels::load_voxels, Point3};
+
+/// Dense 3D grid of densities covering the unit cube, for heterogeneous media.
+pub struct VoxelGrid {
+    resolution: [usize; 3],
+    values: Vec<f32>,
+    max_value: f64,
+}
+
+impl VoxelGrid {
+    /// Creates a grid from values stored with x varying fastest, then y, then z. Fails if the
+    /// number of values doesn't match the resolution or a value is negative or not finite.
+    pub fn new(resolution: [usize; 3], values: Vec<f32>) -> Result<Self, String> {
+        let count = resolution
+            .iter()
+            .try_fold(1usize, |count, &n| count.checked_mul(n))
+            .ok_or_else(|| "voxel grid too large".to_string())?;
+        if values.len() != count {
+            return Err(format!(
+                "expected {} voxel values, found {}",
+                count,
+                values.len()
+            ));
+        }
+        if let Some(index) = values.iter().position(|v| !(v.is_finite() && *v >= 0.0)) {
+            return Err(format!(
+                "voxel {} has invalid density {}",
+                index, values[index]
+            ));
+        }
+
+        let max_value = values.iter().fold(0.0f32, |max, &v| max.max(v)) as f64;
+        Ok(Self {
+            resolution,
+            values,
+            max_value,
+        })
+    }
+
+    /// Loads a raw grid file: a `VOXELS <nx> <ny> <nz>` text line followed by the values as
+    /// little endian 32 bit floats, in the same order as for [`VoxelGrid::new`].
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let (resolution, values) = load_voxels(path)?;
+        Self::new(resolution, values).map_err(|message| {
+            io::Error::new(
+                ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), message),
+            )
+        })
+    }
+
+    /// Get the grid's number of voxels along each axis.
+    pub fn resolution(&self) -> [usize; 3] {
+        self.resolution
+    }
+
+    /// Get the grid's largest value, which bounds every lookup.
+    pub fn max_value(&self) -> f64 {
+        self.max_value
+    }
+
+    /// Trilinearly interpolated value at `p` in `[0, 1]^3`, zero outside of it.
+    pub fn value(&self, p: &Point3) -> f64 {
+        if self.values.is_empty() || (0..3).any(|a| !(0.0..=1.0).contains(&p[a])) {
+            return 0.0;
+        }
+
+        // Voxel centers are at half-integer coordinates; clamp to the outermost ones
+        let mut cell = [0; 3];
+        let mut fraction = [0.0; 3];
+        for a in 0..3 {
+            let n = self.resolution[a];
+            let x = (p[a] * n as f64 - 0.5).clamp(0.0, (n - 1) as f64);
+            cell[a] = (x as usize).min(n.saturating_sub(2));
+            fraction[a] = x - cell[a] as f64;
+        }
+
+        let voxel = |dx: usize, dy: usize, dz: usize| {
+            let x = (cell[0] + dx).min(self.resolution[0] - 1);
+            let y = (cell[1] + dy).min(self.resolution[1] - 1);
+            let z = (cell[2] + dz).min(self.resolution[2] - 1);
+            self.values[(z * self.resolution[1] + y) * self.resolution[0] + x] as f64
+        };
+        let lerp = |a: f64, b: f64, t: f64| (1.0 - t) * a + t * b;
+
+        let [fx, fy, fz] = fraction;
+        lerp(
+            lerp(
+                lerp(voxel(0, 0, 0), voxel(1, 0, 0), fx),
+                lerp(voxel(0, 1, 0), voxel(1, 1, 0), fx),
+                fy,
+            ),
+            lerp(
+                lerp(voxel(0, 0, 1), voxel(1, 0, 1), fx),
+                lerp(voxel(0, 1, 1), voxel(1, 1, 1), fx),
+                fy,
+            ),
+            fz,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_value(grid: &VoxelGrid, p: Point3, expected: f64) {
+        let value = grid.value(&p);
+        assert!((value - expected).abs() < 1e-9, "{} != {}", value, expected);
+    }
+
+    #[test]
+    fn interpolates_between_voxel_centers() {
+        let grid = VoxelGrid::new([2, 1, 1], vec![1.0, 3.0]).unwrap();
+        assert_value(&grid, Point3::new(0.25, 0.5, 0.5), 1.0);
+        assert_value(&grid, Point3::new(0.75, 0.5, 0.5), 3.0);
+        assert_value(&grid, Point3::new(0.5, 0.5, 0.5), 2.0);
+
+        let values = (0..8).map(|i| i as f32).collect();
+        let grid = VoxelGrid::new([2, 2, 2], values).unwrap();
+        assert_value(&grid, Point3::new(0.75, 0.25, 0.75), 5.0);
+        assert_value(&grid, Point3::new(0.25, 0.75, 0.25), 2.0);
+        assert_value(&grid, Point3::new(0.5, 0.5, 0.5), 3.5);
+    }
+
+    #[test]
+    fn clamps_to_outer_voxels_at_cube_edges() {
+        let grid = VoxelGrid::new([2, 1, 1], vec![1.0, 3.0]).unwrap();
+        assert_value(&grid, Point3::new(0.0, 0.0, 0.0), 1.0);
+        assert_value(&grid, Point3::new(1.0, 1.0, 1.0), 3.0);
+        assert_value(&grid, Point3::new(0.1, 1.0, 0.0), 1.0);
+        assert_value(&grid, Point3::new(1.01, 0.5, 0.5), 0.0);
+        assert_value(&grid, Point3::new(0.5, -0.01, 0.5), 0.0);
+    }
+
+    #[test]
+    fn single_voxel_is_constant() {
+        let grid = VoxelGrid::new([1, 1, 1], vec![0.7]).unwrap();
+        for p in [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(0.5, 0.5, 0.5),
+            Point3::new(1.0, 0.2, 1.0),
+        ] {
+            assert_value(&grid, p, 0.7f32 as f64);
+        }
+        assert_eq!(grid.max_value(), 0.7f32 as f64);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        for invalid in [-1.0, f32::NAN, f32::INFINITY] {
+            let error = VoxelGrid::new([2, 1, 1], vec![0.5, invalid]).err().unwrap();
+            assert!(error.starts_with("voxel 1 "), "{}", error);
+        }
+        let error = VoxelGrid::new([2, 2, 1], vec![0.5]).err().unwrap();
+        assert_eq!(error, "expected 4 voxel values, found 1");
+        let error = VoxelGrid::new([usize::MAX, 2, 1], vec![]).err().unwrap();
+        assert_eq!(error, "voxel grid too large");
+    }
+}